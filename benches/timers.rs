@@ -0,0 +1,75 @@
+use bevy::{ecs::system::Command, prelude::*, time::TimeUpdateStrategy};
+use criterion::{criterion_group, criterion_main, BatchSize, Criterion};
+use pecs::prelude::*;
+use std::time::Duration;
+
+const TIMERS: u64 = 100_000;
+
+fn app() -> App {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).add_plugins(PecsPlugin);
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+    app.update();
+    app
+}
+
+/// Registers `TIMERS` timers with deadlines spread over `span`
+fn timers(app: &mut App, span: Duration) -> Vec<PromiseHandle> {
+    (0..TIMERS)
+        .map(|i| {
+            let mut promise = asyn::timeout(span.mul_f64(i as f64 / TIMERS as f64));
+            let handle = promise.handle();
+            promise.apply(&mut app.world);
+            handle
+        })
+        .collect()
+}
+
+fn pending(c: &mut Criterion) {
+    let mut app = app();
+    timers(&mut app, Duration::from_secs(3600));
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_micros(1)));
+    c.bench_function("100k pending timers frame", |b| b.iter(|| app.update()));
+}
+
+fn register(c: &mut Criterion) {
+    c.bench_function("register 100k timers", |b| {
+        b.iter_batched(
+            app,
+            |mut app| timers(&mut app, Duration::from_secs(3600)),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn resolve(c: &mut Criterion) {
+    c.bench_function("resolve 100k timers", |b| {
+        b.iter_batched(
+            || {
+                let mut app = app();
+                timers(&mut app, Duration::ZERO);
+                app
+            },
+            |mut app| app.update(),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+fn discard(c: &mut Criterion) {
+    c.bench_function("discard 100k timers", |b| {
+        b.iter_batched(
+            || {
+                let mut app = app();
+                let handles = timers(&mut app, Duration::from_secs(3600));
+                handles.iter().for_each(PromiseHandle::cancel);
+                app
+            },
+            |mut app| app.update(),
+            BatchSize::LargeInput,
+        )
+    });
+}
+
+criterion_group!(benches, pending, register, resolve, discard);
+criterion_main!(benches);
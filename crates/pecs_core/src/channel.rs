@@ -0,0 +1,378 @@
+//! Pass values between promise chains and systems
+//! ```ignore
+//! #[derive(Resource)]
+//! struct Lines(Sender<String>);
+//!
+//! fn setup(mut commands: Commands) {
+//!     let (sender, receiver) = pecs::bounded(4);
+//!     commands.insert_resource(Lines(sender));
+//!     commands.add(Promise::repeat(
+//!         receiver,
+//!         asyn!(s => {
+//!             s.value.recv().map(|_| s.value).then(asyn!(s, line => match line {
+//!                 Some(line) => {
+//!                     info!("Speaker: {line}");
+//!                     s.asyn().timeout(2.0).with_result(Repeat::Continue)
+//!                 }
+//!                 None => s.asyn().timeout(0.).with_result(Repeat::Break(())),
+//!             }))
+//!         }),
+//!     ));
+//! }
+//!
+//! fn talk(lines: Res<Lines>, keys: Res<Input<KeyCode>>) {
+//!     if keys.just_pressed(KeyCode::Space) {
+//!         if let Err(err) = lines.0.try_send("Hello!".into()) {
+//!             warn!("Speaker is busy: {err}");
+//!         }
+//!     }
+//! }
+//! ```
+use std::collections::VecDeque;
+
+use super::*;
+
+/// Creates unbounded channel. Values sent with [`Sender`] are received
+/// with [`Receiver::recv()`] in the same order.
+pub fn channel<T: 'static + Send + Sync>() -> (Sender<T>, Receiver<T>) {
+    new_channel(None)
+}
+
+/// Creates channel holding no more than `capacity` values. [`Sender::try_send()`] fails
+/// and [`Sender::send()`] waits when the channel is full.
+pub fn bounded<T: 'static + Send + Sync>(capacity: usize) -> (Sender<T>, Receiver<T>) {
+    new_channel(Some(capacity.max(1)))
+}
+
+fn new_channel<T: 'static + Send + Sync>(capacity: Option<usize>) -> (Sender<T>, Receiver<T>) {
+    let shared = Arc::new(Mutex::new(Shared {
+        queue: VecDeque::new(),
+        capacity,
+        senders: 1,
+        closed: false,
+        receiver: true,
+        waiting_senders: VecDeque::new(),
+        waiting_receivers: VecDeque::new(),
+        woken: None,
+    }));
+    (Sender(shared.clone()), Receiver(shared))
+}
+
+/// Error returned by [`Sender::try_send()`], holds the value that was not sent
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrySendError<T> {
+    /// The bounded channel is full
+    Full(T),
+    /// The channel is closed or the receiver is dropped
+    Closed(T),
+}
+
+impl<T> TrySendError<T> {
+    pub fn into_inner(self) -> T {
+        match self {
+            TrySendError::Full(value) | TrySendError::Closed(value) => value,
+        }
+    }
+}
+
+impl<T> std::fmt::Display for TrySendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TrySendError::Full(_) => write!(f, "channel is full"),
+            TrySendError::Closed(_) => write!(f, "channel is closed"),
+        }
+    }
+}
+
+/// Sending half of the [`channel()`]. Can be cloned and stored in resources or components.
+/// The channel closes when every sender is dropped.
+pub struct Sender<T>(Arc<Mutex<Shared<T>>>);
+
+impl<T: 'static + Send + Sync> Sender<T> {
+    /// Sends `value` right away, can be called from systems
+    pub fn try_send(&self, value: T) -> Result<(), TrySendError<T>> {
+        let mut channel = self.0.lock().unwrap();
+        channel.try_send(value)?;
+        channel.wake();
+        Ok(())
+    }
+
+    /// Create new [`Promise`] that resolves when `value` is sent. Waits while the bounded
+    /// channel is full, resolves with `Err(value)` if the channel gets closed.
+    #[track_caller]
+    pub fn send(&self, value: T) -> Promise<(), Result<(), T>> {
+        let shared = self.0.clone();
+        let discard = self.0.clone();
+        Promise::register(
+            move |world, id| {
+                let mut channel = shared.lock().unwrap();
+                let mut value = Some(value);
+                if channel.waiting_senders.is_empty() {
+                    let sent: Result<(), T> = match channel.try_send(value.take().unwrap()) {
+                        Ok(()) => Ok(()),
+                        Err(TrySendError::Closed(value)) => Err(value),
+                        Err(TrySendError::Full(full)) => {
+                            value = Some(full);
+                            Ok(())
+                        }
+                    };
+                    if value.is_none() {
+                        channel.wake();
+                        return resolve_unlocked(world, id, channel, sent);
+                    }
+                }
+                let mut channels = world.resource_mut::<Channels>();
+                channel.woken = Some(channels.woken.clone());
+                channel.waiting_senders.push_back(id);
+                drop(channel);
+                let shared = shared.clone();
+                channels.waiters.insert(
+                    id,
+                    Box::new(move |id, commands| {
+                        let mut channel = shared.lock().unwrap();
+                        if channel.waiting_senders.front() != Some(&id) {
+                            return false;
+                        }
+                        let result = match channel.try_send(value.take().unwrap()) {
+                            Ok(()) => Ok(()),
+                            Err(TrySendError::Closed(value)) => Err(value),
+                            Err(TrySendError::Full(full)) => {
+                                value = Some(full);
+                                return false;
+                            }
+                        };
+                        channel.waiting_senders.pop_front();
+                        channel.wake();
+                        commands.add(PromiseCommand::resolve(id, result));
+                        true
+                    }),
+                );
+            },
+            move |world, id| {
+                world.resource_mut::<Channels>().waiters.remove(&id);
+                let mut channel = discard.lock().unwrap();
+                channel.waiting_senders.retain(|waiting| *waiting != id);
+                channel.wake();
+            },
+        )
+        .awaits(PromiseAwaits::Channel)
+    }
+
+    /// Closes the channel, the receiver gets the values sent before
+    pub fn close(&self) {
+        let mut channel = self.0.lock().unwrap();
+        channel.closed = true;
+        channel.wake();
+    }
+
+    /// Returns `true` if the channel is closed or the receiver is dropped
+    pub fn is_closed(&self) -> bool {
+        let channel = self.0.lock().unwrap();
+        channel.closed || !channel.receiver
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        self.0.lock().unwrap().senders += 1;
+        Sender(self.0.clone())
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut channel = self.0.lock().unwrap();
+        channel.senders -= 1;
+        channel.wake();
+    }
+}
+
+/// Receiving half of the [`channel()`]. Dropping the receiver closes the channel.
+pub struct Receiver<T>(Arc<Mutex<Shared<T>>>);
+
+impl<T: 'static + Send + Sync> Receiver<T> {
+    /// Create new [`Promise`] resolving with the next value, or with `None` when the channel
+    /// is closed and every sent value is received. Pending promises resolve in the order they
+    /// were registered, not the order they were created in.
+    #[track_caller]
+    pub fn recv(&self) -> Promise<(), Option<T>> {
+        let shared = self.0.clone();
+        let discard = self.0.clone();
+        Promise::register(
+            move |world, id| {
+                let mut channel = shared.lock().unwrap();
+                if channel.waiting_receivers.is_empty() {
+                    if let Some(value) = channel.try_recv() {
+                        channel.wake();
+                        return resolve_unlocked(world, id, channel, value);
+                    }
+                }
+                let mut channels = world.resource_mut::<Channels>();
+                channel.woken = Some(channels.woken.clone());
+                channel.waiting_receivers.push_back(id);
+                drop(channel);
+                let shared = shared.clone();
+                channels.waiters.insert(
+                    id,
+                    Box::new(move |id, commands| {
+                        let mut channel = shared.lock().unwrap();
+                        if channel.waiting_receivers.front() != Some(&id) {
+                            return false;
+                        }
+                        let Some(value) = channel.try_recv() else {
+                            return false;
+                        };
+                        channel.waiting_receivers.pop_front();
+                        channel.wake();
+                        commands.add(PromiseCommand::resolve(id, value));
+                        true
+                    }),
+                );
+            },
+            move |world, id| {
+                world.resource_mut::<Channels>().waiters.remove(&id);
+                let mut channel = discard.lock().unwrap();
+                channel.waiting_receivers.retain(|waiting| *waiting != id);
+                channel.wake();
+            },
+        )
+        .awaits(PromiseAwaits::Channel)
+    }
+
+    /// Takes the next value if it is already sent, can be called from systems
+    pub fn try_recv(&self) -> Option<T> {
+        let mut channel = self.0.lock().unwrap();
+        let value = channel.queue.pop_front()?;
+        channel.wake();
+        Some(value)
+    }
+
+    /// Returns `true` if the channel is closed and every sent value is received
+    pub fn is_closed(&self) -> bool {
+        let channel = self.0.lock().unwrap();
+        channel.is_closed() && channel.queue.is_empty()
+    }
+
+    /// Create new [`PromiseStream`][crate::stream::PromiseStream] of the received values
+    pub fn into_stream(self) -> crate::stream::PromiseStream<(), T> {
+        crate::stream::PromiseStream::from_pull(Box::new(move || self.recv().awaits(PromiseAwaits::Stream)))
+    }
+}
+
+impl<T> Drop for Receiver<T> {
+    fn drop(&mut self) {
+        let mut channel = self.0.lock().unwrap();
+        channel.receiver = false;
+        channel.queue.clear();
+        channel.wake();
+    }
+}
+
+struct Shared<T> {
+    queue: VecDeque<T>,
+    capacity: Option<usize>,
+    senders: usize,
+    closed: bool,
+    receiver: bool,
+    /// pending promises in the order of registration, served first to keep the order
+    waiting_senders: VecDeque<PromiseId>,
+    waiting_receivers: VecDeque<PromiseId>,
+    /// [`Channels::woken`] of the world the pending promises are registered in
+    woken: Option<Woken>,
+}
+
+impl<T> Shared<T> {
+    fn is_closed(&self) -> bool {
+        self.closed || self.senders == 0 || !self.receiver
+    }
+    fn is_full(&self) -> bool {
+        self.capacity.is_some_and(|capacity| self.queue.len() >= capacity)
+    }
+    /// Wakes the first pending promise of each side if it can make progress
+    fn wake(&self) {
+        let Some(woken) = &self.woken else {
+            return;
+        };
+        let mut woken = woken.lock().unwrap();
+        if !self.queue.is_empty() || self.is_closed() {
+            woken.extend(self.waiting_receivers.front());
+        }
+        if self.closed || !self.receiver || !self.is_full() {
+            woken.extend(self.waiting_senders.front());
+        }
+    }
+    fn try_send(&mut self, value: T) -> Result<(), TrySendError<T>> {
+        if self.closed || !self.receiver {
+            Err(TrySendError::Closed(value))
+        } else if self.is_full() {
+            Err(TrySendError::Full(value))
+        } else {
+            self.queue.push_back(value);
+            Ok(())
+        }
+    }
+    /// `Some(None)` if the channel is closed, `None` if the value is not sent yet
+    fn try_recv(&mut self) -> Option<Option<T>> {
+        match self.queue.pop_front() {
+            Some(value) => Some(Some(value)),
+            None if self.is_closed() => Some(None),
+            None => None,
+        }
+    }
+}
+
+/// Resolves the promise after the channel lock is released
+fn resolve_unlocked<T, R: 'static + Send>(
+    world: &mut World,
+    id: PromiseId,
+    channel: std::sync::MutexGuard<Shared<T>>,
+    result: R,
+) {
+    drop(channel);
+    promise_resolve::<(), R>(world, id, (), result);
+}
+
+type ChannelPoll = Box<dyn FnMut(PromiseId, &mut Commands) -> bool + Send + Sync>;
+type Woken = Arc<Mutex<VecDeque<PromiseId>>>;
+
+/// Pending [`Sender::send()`] and [`Receiver::recv()`] promises. Channels wake the first
+/// pending promise when it can make progress, [`process_channels`] polls only the woken ones.
+#[derive(Resource, Default)]
+pub struct Channels {
+    waiters: HashMap<PromiseId, ChannelPoll>,
+    woken: Woken,
+}
+
+impl Channels {
+    /// Number of pending promises
+    pub fn len(&self) -> usize {
+        self.waiters.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.waiters.is_empty()
+    }
+}
+
+pub fn process_channels(mut channels: ResMut<Channels>, mut commands: Commands) {
+    let channels = &mut *channels;
+    // resolved promises wake the next ones, which are polled in the same run
+    loop {
+        let next = channels.woken.lock().unwrap().pop_front();
+        let Some(id) = next else {
+            break;
+        };
+        if let Some(poll) = channels.waiters.get_mut(&id) {
+            if poll(id, &mut commands) {
+                channels.waiters.remove(&id);
+            }
+        }
+    }
+}
+
+pub struct PromiseChannelPlugin;
+impl Plugin for PromiseChannelPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Channels>();
+        PromiseSchedule::add_systems(app, process_channels);
+    }
+}
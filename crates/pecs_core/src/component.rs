@@ -0,0 +1,195 @@
+//! Promises watching the component of the single entity
+//! ```ignore
+//! fn setup(mut commands: Commands, boss: Query<Entity, With<Boss>>) {
+//!     let boss = boss.single();
+//!     commands.add(
+//!         asyn::component::until(boss, |h: &Health| h.0 <= 0.)
+//!             .then(asyn!(_, health => match health {
+//!                 Ok(_) => info!("Boss defeated"),
+//!                 Err(Despawned(_)) => info!("Boss left the arena"),
+//!             })),
+//!     );
+//! }
+//! ```
+use bevy::ecs::component::Tick;
+
+use super::*;
+
+pub mod asyn {
+    use super::{register_waiter, Despawned, Promise, Wait};
+    use bevy::prelude::*;
+
+    /// Creates promise that resolves with the `C` component added to the `entity`
+    /// after the promise is registered.
+    #[track_caller]
+    pub fn added<C: Component + Clone>(entity: Entity) -> Promise<(), Result<C, Despawned>> {
+        register_waiter::<C, C>(entity, Wait::Added)
+    }
+
+    /// Creates promise that resolves with the `C` component of the `entity` when it is
+    /// added or mutably accessed after the promise is registered.
+    #[track_caller]
+    pub fn changed<C: Component + Clone>(entity: Entity) -> Promise<(), Result<C, Despawned>> {
+        register_waiter::<C, C>(entity, Wait::Changed)
+    }
+
+    /// Creates promise that resolves when the `entity` has no `C` component. Despawning the
+    /// `entity` resolves the promise with [`Despawned`] error.
+    #[track_caller]
+    pub fn removed<C: Component + Clone>(entity: Entity) -> Promise<(), Result<(), Despawned>> {
+        register_waiter::<C, ()>(entity, Wait::Removed)
+    }
+
+    /// Creates promise that resolves with the `C` component of the `entity` when
+    /// `predicate` returns `true` for it. The component is checked once per frame.
+    #[track_caller]
+    pub fn until<C: Component + Clone>(
+        entity: Entity,
+        predicate: impl 'static + Fn(&C) -> bool + Send + Sync,
+    ) -> Promise<(), Result<C, Despawned>> {
+        register_waiter(entity, Wait::Until(Box::new(predicate)))
+    }
+}
+
+/// Error returned by [`asyn`] promises when the watched entity is despawned first
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Despawned(pub Entity);
+
+impl std::fmt::Display for Despawned {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} is despawned", self.0)
+    }
+}
+
+impl std::error::Error for Despawned {}
+
+type ComponentPredicate<C> = Box<dyn Fn(&C) -> bool + Send + Sync>;
+
+enum Wait<C> {
+    Added,
+    Changed,
+    Removed,
+    Until(ComponentPredicate<C>),
+}
+
+struct ComponentWaiter<C> {
+    entity: Entity,
+    /// changes made before the promise is registered are ignored
+    since: Tick,
+    wait: Wait<C>,
+}
+
+/// Promises waiting for the `C` component.
+#[derive(Resource)]
+pub struct ComponentWaiters<C: Component>(HashMap<PromiseId, ComponentWaiter<C>>);
+impl<C: Component> Default for ComponentWaiters<C> {
+    fn default() -> Self {
+        ComponentWaiters(HashMap::new())
+    }
+}
+impl<C: Component> ComponentWaiters<C> {
+    /// Number of promises waiting for the component
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+/// Removed waiters resolve with `Result<(), Despawned>`, others with `Result<C, Despawned>`
+#[track_caller]
+fn register_waiter<C: Component + Clone, R: 'static + Send>(
+    entity: Entity,
+    wait: Wait<C>,
+) -> Promise<(), Result<R, Despawned>> {
+    Promise::register(
+        move |world, id| {
+            if !world.contains_resource::<ComponentWaiters<C>>() {
+                world.init_resource::<ComponentWaiters<C>>();
+                PromiseResolvers::add(world, resolve_waiters::<C>);
+            }
+            let since = world.increment_change_tick();
+            world
+                .resource_mut::<ComponentWaiters<C>>()
+                .0
+                .insert(id, ComponentWaiter { entity, since, wait });
+        },
+        move |world, id| {
+            if let Some(mut waiters) = world.get_resource_mut::<ComponentWaiters<C>>() {
+                waiters.0.remove(&id);
+            }
+        },
+    )
+    .awaits(PromiseAwaits::Component)
+}
+
+fn resolve_waiters<C: Component + Clone>(world: &mut World) {
+    let this_run = world.change_tick();
+    let mut resolved = vec![];
+    world.resource_scope(|world, mut waiters: Mut<ComponentWaiters<C>>| {
+        waiters.0.retain(|id, waiter| {
+            let removed = matches!(waiter.wait, Wait::Removed);
+            let Some(entity) = world.get_entity(waiter.entity) else {
+                resolved.push((*id, removed, Err(Despawned(waiter.entity))));
+                return false;
+            };
+            let component = entity.get::<C>();
+            let ticks = entity.get_change_ticks::<C>();
+            let result = match (&waiter.wait, component, ticks) {
+                (Wait::Removed, None, _) => None,
+                (Wait::Added, Some(c), Some(t)) if t.is_added(waiter.since, this_run) => Some(c.clone()),
+                (Wait::Changed, Some(c), Some(t)) if t.is_changed(waiter.since, this_run) => Some(c.clone()),
+                (Wait::Until(predicate), Some(c), _) if predicate(c) => Some(c.clone()),
+                _ => return true,
+            };
+            resolved.push((*id, removed, Ok(result)));
+            false
+        });
+    });
+    for (id, removed, result) in resolved {
+        match (removed, result) {
+            (true, result) => promise_resolve::<(), Result<(), Despawned>>(world, id, (), result.map(|_| ())),
+            (false, Ok(Some(component))) => promise_resolve::<(), Result<C, Despawned>>(world, id, (), Ok(component)),
+            (false, Ok(None)) => unreachable!("only removed waiters resolve without the component"),
+            (false, Err(err)) => promise_resolve::<(), Result<C, Despawned>>(world, id, (), Err(err)),
+        }
+    }
+}
+
+pub struct StatefulAsynComponents<S>(S);
+impl<S: 'static + Send> StatefulAsynComponents<S> {
+    /// Stateful version of [`asyn::added()`]
+    #[track_caller]
+    pub fn added<C: Component + Clone>(self, entity: Entity) -> Promise<S, Result<C, Despawned>> {
+        asyn::added(entity).with(self.0)
+    }
+    /// Stateful version of [`asyn::changed()`]
+    #[track_caller]
+    pub fn changed<C: Component + Clone>(self, entity: Entity) -> Promise<S, Result<C, Despawned>> {
+        asyn::changed(entity).with(self.0)
+    }
+    /// Stateful version of [`asyn::removed()`]
+    #[track_caller]
+    pub fn removed<C: Component + Clone>(self, entity: Entity) -> Promise<S, Result<(), Despawned>> {
+        asyn::removed::<C>(entity).with(self.0)
+    }
+    /// Stateful version of [`asyn::until()`]
+    #[track_caller]
+    pub fn until<C: Component + Clone>(
+        self,
+        entity: Entity,
+        predicate: impl 'static + Fn(&C) -> bool + Send + Sync,
+    ) -> Promise<S, Result<C, Despawned>> {
+        asyn::until(entity, predicate).with(self.0)
+    }
+}
+
+pub trait ComponentOpsExtension<S> {
+    fn components(self) -> StatefulAsynComponents<S>;
+}
+impl<S: 'static + Send> ComponentOpsExtension<S> for AsynOps<S> {
+    fn components(self) -> StatefulAsynComponents<S> {
+        StatefulAsynComponents(self.0)
+    }
+}
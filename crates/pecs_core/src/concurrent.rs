@@ -0,0 +1,205 @@
+//! Map collections to promises keeping a limited number of them in flight
+//! ```ignore
+//! fn setup(mut commands: Commands) {
+//!     let urls = (0..500).map(|i| format!("https://example.com/thumbnails/{i}.png"));
+//!     commands.add(
+//!         // no more than 8 requests at once
+//!         Promise::try_map_concurrent(urls, 8, asyn!(url => asyn::http::get(&url.value).send()))
+//!             .then(asyn!(_, thumbnails => match thumbnails {
+//!                 Ok(thumbnails) => info!("Downloaded {} thumbnails", thumbnails.len()),
+//!                 Err(err) => error!("Download failed: {err}"),
+//!             })),
+//!     );
+//! }
+//! ```
+use std::collections::VecDeque;
+
+use super::*;
+
+type Func<T, O, P> = Asyn<(PromiseState<T>, ()), O, P>;
+
+/// Progress of the single map, lives in the world until the map resolves or gets discarded.
+struct Concurrent<T, R, O: 'static, P: PromiseParams> {
+    items: VecDeque<(usize, T)>,
+    func: Func<T, O, P>,
+    limit: usize,
+    in_flight: Vec<(usize, PromiseId)>,
+    results: Vec<Option<R>>,
+    done: usize,
+    fail: fn(&R) -> bool,
+    pumping: bool,
+}
+
+impl<R: 'static + Send> Promise<(), Vec<R>> {
+    /// Create new [`Promise`] that calls `func` for every item and awaits returned promises
+    /// keeping no more than `limit` of them in flight. Resolves with results in the order
+    /// of `items` when every promise resolves.
+    #[track_caller]
+    pub fn map_concurrent<
+        T: 'static + Send,
+        S: 'static + Send,
+        O: 'static + Into<PromiseResult<S, R>>,
+        P: PromiseParams,
+    >(
+        items: impl IntoIterator<Item = T>,
+        limit: usize,
+        func: Func<T, O, P>,
+    ) -> Promise<(), Vec<R>> {
+        map_concurrent::<T, S, R, O, P>(items, limit, func, |_| false)
+            .map_result(|results| results.unwrap_or_else(|_| unreachable!("collect-all map can't fail")))
+    }
+}
+
+impl<R: 'static + Send, E: 'static + Send> Promise<(), Result<Vec<R>, E>> {
+    /// Same as [`map_concurrent()`][Promise::map_concurrent], but resolves with the first `Err`
+    /// right away. Promises in flight are discarded and remaining items are not started.
+    #[track_caller]
+    pub fn try_map_concurrent<
+        T: 'static + Send,
+        S: 'static + Send,
+        O: 'static + Into<PromiseResult<S, Result<R, E>>>,
+        P: PromiseParams,
+    >(
+        items: impl IntoIterator<Item = T>,
+        limit: usize,
+        func: Func<T, O, P>,
+    ) -> Promise<(), Result<Vec<R>, E>> {
+        map_concurrent::<T, S, Result<R, E>, O, P>(items, limit, func, Result::is_err).map_result(|results| {
+            match results {
+                Ok(results) => Ok(results.into_iter().filter_map(Result::ok).collect()),
+                Err(Err(err)) => Err(err),
+                Err(Ok(_)) => unreachable!("only errors fail the map"),
+            }
+        })
+    }
+}
+
+/// Resolves with every result in the input order, or with the first result `fail` returns `true` for
+#[track_caller]
+fn map_concurrent<
+    T: 'static + Send,
+    S: 'static + Send,
+    R: 'static + Send,
+    O: 'static + Into<PromiseResult<S, R>>,
+    P: PromiseParams,
+>(
+    items: impl IntoIterator<Item = T>,
+    limit: usize,
+    func: Func<T, O, P>,
+    fail: fn(&R) -> bool,
+) -> Promise<(), Result<Vec<R>, R>> {
+    let items: VecDeque<_> = items.into_iter().enumerate().collect();
+    Promise::register(
+        move |world, id| {
+            if items.is_empty() {
+                promise_resolve::<(), Result<Vec<R>, R>>(world, id, (), Ok(vec![]));
+                return;
+            }
+            let results = (0..items.len()).map(|_| None).collect();
+            Combined::insert(
+                world,
+                id,
+                Concurrent {
+                    items,
+                    func,
+                    limit: limit.max(1),
+                    in_flight: vec![],
+                    results,
+                    done: 0,
+                    fail,
+                    pumping: false,
+                },
+            );
+            pump::<T, S, R, O, P>(world, id);
+        },
+        move |world, id| {
+            if let Some(map) = Combined::<Concurrent<T, R, O, P>>::remove(world, id) {
+                for (_, promise) in map.in_flight {
+                    promise_discard::<(), ()>(world, promise);
+                }
+            }
+        },
+    )
+}
+
+/// Starts items until the limit is reached
+fn pump<
+    T: 'static + Send,
+    S: 'static + Send,
+    R: 'static + Send,
+    O: 'static + Into<PromiseResult<S, R>>,
+    P: PromiseParams,
+>(
+    world: &mut World,
+    map_id: PromiseId,
+) {
+    match Combined::<Concurrent<T, R, O, P>>::get_mut(world, map_id) {
+        Some(map) if !map.pumping => map.pumping = true,
+        _ => return,
+    }
+    // promises resolved synchronously only record their results here,
+    // so long maps don't grow the stack
+    loop {
+        let Some(map) = Combined::<Concurrent<T, R, O, P>>::get_mut(world, map_id) else {
+            return;
+        };
+        if map.in_flight.len() >= map.limit {
+            break;
+        }
+        let Some((idx, item)) = map.items.pop_front() else {
+            break;
+        };
+        let promise = Promise::new(item, map.func.clone())
+            .map(move |_| (map_id, idx))
+            .then(asyn!(|s, r| {
+                let (map_id, idx) = s.value;
+                Promise::<(), ()>::register(
+                    move |world, id| {
+                        complete::<T, S, R, O, P>(world, map_id, idx, r);
+                        promise_resolve::<(), ()>(world, id, (), ());
+                    },
+                    |_, _| {},
+                )
+            }));
+        map.in_flight.push((idx, promise.id));
+        promise_register(world, promise);
+    }
+    if let Some(map) = Combined::<Concurrent<T, R, O, P>>::get_mut(world, map_id) {
+        map.pumping = false;
+    }
+}
+
+fn complete<
+    T: 'static + Send,
+    S: 'static + Send,
+    R: 'static + Send,
+    O: 'static + Into<PromiseResult<S, R>>,
+    P: PromiseParams,
+>(
+    world: &mut World,
+    map_id: PromiseId,
+    idx: usize,
+    result: R,
+) {
+    let Some(map) = Combined::<Concurrent<T, R, O, P>>::get_mut(world, map_id) else {
+        return;
+    };
+    map.in_flight.retain(|(i, _)| *i != idx);
+    if (map.fail)(&result) {
+        let map = Combined::<Concurrent<T, R, O, P>>::remove(world, map_id).unwrap();
+        for (_, promise) in map.in_flight {
+            promise_discard::<(), ()>(world, promise);
+        }
+        promise_resolve::<(), Result<Vec<R>, R>>(world, map_id, (), Err(result));
+        return;
+    }
+    map.results[idx] = Some(result);
+    map.done += 1;
+    if map.done == map.results.len() {
+        let map = Combined::<Concurrent<T, R, O, P>>::remove(world, map_id).unwrap();
+        let results = map.results.into_iter().map(Option::unwrap).collect();
+        promise_resolve::<(), Result<Vec<R>, R>>(world, map_id, (), Ok(results));
+    } else {
+        pump::<T, S, R, O, P>(world, map_id);
+    }
+}
@@ -0,0 +1,109 @@
+//! Promises waiting for the world to match the condition
+//! ```ignore
+//! fn setup(mut commands: Commands) {
+//!     commands.add(
+//!         asyn::until(asyn!(bosses: Query<&Health, With<Boss>> => bosses.iter().all(|h| h.0 <= 0.)))
+//!             .then(asyn!(_ => info!("Level cleared"))),
+//!     );
+//! }
+//! ```
+//! Conditions are regular [`Asyn`] functions with the cached system state, so `Changed`/`Added`
+//! filters and `Local`s keep working between the checks.
+use std::collections::BTreeMap;
+
+use super::*;
+
+/// Creates promise that checks `condition` when registered and then once per frame,
+/// resolving when it returns `true`.
+#[track_caller]
+pub fn until<P: PromiseParams>(condition: Asyn<(PromiseState<()>, ()), bool, P>) -> Promise<(), ()> {
+    wait(move |world| condition.run((PromiseState::new(()), ()), world).then_some(()))
+}
+
+/// Creates promise that checks `condition` when registered and then once per frame,
+/// resolving with the first `Some` value it returns.
+#[track_caller]
+pub fn until_some<T: 'static + Send, P: PromiseParams>(
+    condition: Asyn<(PromiseState<()>, ()), Option<T>, P>,
+) -> Promise<(), T> {
+    wait(move |world| condition.run((PromiseState::new(()), ()), world))
+}
+
+#[track_caller]
+fn wait<T: 'static + Send, F: 'static + Send + FnMut(&mut World) -> Option<T> + Send + Sync>(
+    mut check: F,
+) -> Promise<(), T> {
+    Promise::register(
+        move |world, id| {
+            let mut check: ConditionCheck = Box::new(move |world, id| {
+                let Some(value) = check(world) else {
+                    return false;
+                };
+                promise_resolve::<(), T>(world, id, (), value);
+                true
+            });
+            if !check(world, id) {
+                world.resource_mut::<Conditions>().insert(id, check);
+            }
+        },
+        move |world, id| {
+            world.resource_mut::<Conditions>().remove(&id);
+        },
+    )
+    .awaits(PromiseAwaits::Condition)
+}
+
+type ConditionCheck = Box<dyn FnMut(&mut World, PromiseId) -> bool + Send + Sync>;
+
+/// Conditions of the pending [`until()`] and [`until_some()`] promises, checked
+/// every frame by [`process_conditions`] in the order they were registered.
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct Conditions(BTreeMap<PromiseId, ConditionCheck>);
+
+pub fn process_conditions(world: &mut World) {
+    let pending: Vec<_> = world.resource::<Conditions>().keys().copied().collect();
+    for id in pending {
+        // the condition is taken out while running, so the resolved chain
+        // may discard or register other conditions
+        let Some(mut check) = world.resource_mut::<Conditions>().remove(&id) else {
+            continue;
+        };
+        if !check(world, id) {
+            world.resource_mut::<Conditions>().insert(id, check);
+        }
+    }
+}
+
+pub struct PromiseConditionPlugin;
+impl Plugin for PromiseConditionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Conditions>();
+        PromiseSchedule::add_systems(app, process_conditions);
+    }
+}
+
+pub struct StatefulAsynConditions<S>(S);
+impl<S: 'static + Send> StatefulAsynConditions<S> {
+    /// Stateful version of [`until()`]
+    #[track_caller]
+    pub fn until<P: PromiseParams>(self, condition: Asyn<(PromiseState<()>, ()), bool, P>) -> Promise<S, ()> {
+        until(condition).with(self.0)
+    }
+    /// Stateful version of [`until_some()`]
+    #[track_caller]
+    pub fn until_some<T: 'static + Send, P: PromiseParams>(
+        self,
+        condition: Asyn<(PromiseState<()>, ()), Option<T>, P>,
+    ) -> Promise<S, T> {
+        until_some(condition).with(self.0)
+    }
+}
+
+pub trait ConditionOpsExtension<S> {
+    fn conditions(self) -> StatefulAsynConditions<S>;
+}
+impl<S: 'static + Send> ConditionOpsExtension<S> for AsynOps<S> {
+    fn conditions(self) -> StatefulAsynConditions<S> {
+        StatefulAsynConditions(self.0)
+    }
+}
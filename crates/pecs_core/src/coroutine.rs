@@ -0,0 +1,300 @@
+//! Write promise chains with `async`/`await`
+use bevy::utils::HashSet;
+use std::{
+    cell::Cell,
+    future::{Future, IntoFuture},
+    pin::Pin,
+    ptr,
+    task::{Context, Poll, Wake, Waker},
+};
+
+use super::*;
+use schedule::{CoroutineSet, PromiseSet};
+
+thread_local!(static WORLD: Cell<*mut World> = const { Cell::new(ptr::null_mut()) });
+thread_local!(static CURRENT: Cell<Option<(PromiseId, DiscardFn)>> = const { Cell::new(None) });
+
+/// Creates promise that drives the future returned by `func` and resolves
+/// with its output. The future is polled by pecs after it was woken, so any
+/// [`Promise`] can be awaited inside it, and the [`Cx`] passed to `func` provides
+/// access to the [`World`] and to the [`AsynOps`] extensions:
+/// ```ignore
+/// fn setup(mut commands: Commands) {
+///     commands.add(pecs::spawn(|cx| async move {
+///         cx.timeout(1.0).await;
+///         let response = cx.http().get("https://bevyengine.org").await;
+///         let elapsed = cx.run(asyn!(time: Res<Time> => time.elapsed_seconds())).await;
+///         info!("Got {} bytes in {elapsed:0.2}s", response.map(|r| r.bytes.len()).unwrap_or(0));
+///         asyn::app::exit().await;
+///     }));
+/// }
+/// ```
+/// Discarding the promise drops the future and discards the promise it awaits.
+/// If the awaited promise is discarded, the coroutine is discarded as well.
+#[track_caller]
+pub fn spawn<T: 'static + Send, Fut: 'static + Future<Output = T>, F: 'static + Send + FnOnce(Cx) -> Fut>(
+    func: F,
+) -> Promise<(), T> {
+    Promise::register(
+        move |world, id| {
+            let future = func(Cx::new(id));
+            let coroutine = Coroutine(Box::pin(async move {
+                let result = future.await;
+                with_world(|world| promise_resolve::<(), T>(world, id, (), result));
+            }));
+            Coroutines::get(world).insert(id, coroutine, promise_discard::<(), T>);
+        },
+        move |world, id| {
+            let coroutine = Coroutines::get(world).remove(id);
+            // dropping the future discards the promise it awaits
+            enter(world, move || drop(coroutine));
+        },
+    )
+    .awaits(PromiseAwaits::Coroutine)
+}
+
+pub struct PromiseCoroutinePlugin;
+impl Plugin for PromiseCoroutinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_non_send_resource::<Coroutines>();
+        let schedule = app.world.get_resource::<PromiseSchedule>().copied().unwrap_or_default();
+        app.configure_sets(schedule.0, CoroutineSet.in_set(PromiseSet));
+        app.add_systems(
+            schedule.0,
+            (apply_deferred, drive_coroutines).chain().in_set(CoroutineSet),
+        );
+    }
+}
+
+/// Context of the coroutine created with [`spawn()`]. Dereferences to
+/// [`AsynOps<()>`][AsynOps], so `cx.timeout(1.0)`, `cx.http()` and other
+/// extensions are available.
+#[derive(Clone, Copy)]
+pub struct Cx {
+    id: PromiseId,
+    ops: AsynOps<()>,
+}
+
+impl Cx {
+    fn new(id: PromiseId) -> Cx {
+        Cx { id, ops: AsynOps(()) }
+    }
+    /// Id of the promise created by [`spawn()`]
+    pub fn id(&self) -> PromiseId {
+        self.id
+    }
+    /// Runs the [`Asyn`][struct@Asyn] function with access to system params and
+    /// returns its output.
+    /// ```ignore
+    /// let entity = cx.run(asyn!(mut commands: Commands => commands.spawn_empty().id())).await;
+    /// ```
+    pub fn run<T: 'static + Send, P: PromiseParams>(
+        &self,
+        func: Asyn<(PromiseState<()>, ()), T, P>,
+    ) -> impl Future<Output = T> {
+        async move { with_world(|world| func.run((PromiseState::new(()), ()), world)) }
+    }
+}
+
+impl std::ops::Deref for Cx {
+    type Target = AsynOps<()>;
+    fn deref(&self) -> &Self::Target {
+        &self.ops
+    }
+}
+
+/// Future created from the [`Promise`] when it gets awaited inside [`spawn()`].
+/// The promise is registered on the first poll, the state of the promise is
+/// dropped and the future resolves with the promise result.
+pub struct PromiseFuture<S: 'static + Send, R: 'static + Send> {
+    id: PromiseId,
+    promise: Option<Promise<S, R>>,
+    slot: Arc<Mutex<FutureSlot<R>>>,
+}
+
+struct FutureSlot<R> {
+    result: Option<R>,
+    waker: Option<Waker>,
+    discarded: bool,
+}
+
+impl<S: 'static + Send, R: 'static + Send> IntoFuture for Promise<S, R> {
+    type Output = R;
+    type IntoFuture = PromiseFuture<S, R>;
+    fn into_future(self) -> Self::IntoFuture {
+        PromiseFuture {
+            id: self.id,
+            promise: Some(self),
+            slot: Arc::new(Mutex::new(FutureSlot {
+                result: None,
+                waker: None,
+                discarded: false,
+            })),
+        }
+    }
+}
+
+impl<S: 'static + Send, R: 'static + Send> Future for PromiseFuture<S, R> {
+    type Output = R;
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<R> {
+        let this = self.get_mut();
+        if let Some(mut promise) = this.promise.take() {
+            if promise.resolve.is_some() {
+                error!(
+                    "Misconfigured {}<{}, {}>, resolve already defined",
+                    promise.id,
+                    type_name::<S>(),
+                    type_name::<R>(),
+                );
+                return Poll::Pending;
+            }
+            let slot = this.slot.clone();
+            promise.resolve = Some(Box::new(move |_world, _state, result| {
+                let mut slot = slot.lock().unwrap();
+                slot.result = Some(result);
+                if let Some(waker) = slot.waker.take() {
+                    waker.wake();
+                }
+            }));
+            let slot = this.slot.clone();
+            let coroutine = CURRENT.with(|current| current.get());
+            let discard = mem::take(&mut promise.discard);
+            promise.discard = Some(Box::new(move |world, id| {
+                if let Some(discard) = discard {
+                    discard(world, id);
+                }
+                let discarded = mem::replace(&mut slot.lock().unwrap().discarded, true);
+                if let (false, Some((coroutine, discard))) = (discarded, coroutine) {
+                    discard(world, coroutine);
+                }
+            }));
+            with_world(|world| promise_register::<S, R>(world, promise));
+        }
+        let mut slot = this.slot.lock().unwrap();
+        if let Some(result) = slot.result.take() {
+            slot.discarded = true;
+            Poll::Ready(result)
+        } else {
+            slot.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+impl<S: 'static + Send, R: 'static + Send> Drop for PromiseFuture<S, R> {
+    fn drop(&mut self) {
+        if self.promise.is_some() {
+            return;
+        }
+        let discard = {
+            let mut slot = self.slot.lock().unwrap();
+            !mem::replace(&mut slot.discarded, true) && slot.result.is_none()
+        };
+        if discard {
+            let id = self.id;
+            try_with_world(|world| promise_discard::<S, R>(world, id));
+        }
+    }
+}
+
+/// Futures are not required to be `Send`, so coroutines are kept in the
+/// non-send [`Coroutines`] resource and only polled on the main thread.
+struct Coroutine(Pin<Box<dyn Future<Output = ()>>>);
+
+struct CoroutineWaker {
+    id: PromiseId,
+    woken: Arc<Mutex<HashSet<PromiseId>>>,
+}
+
+impl Wake for CoroutineWaker {
+    fn wake(self: Arc<Self>) {
+        self.woken.lock().unwrap().insert(self.id);
+    }
+}
+
+/// Coroutines created with [`spawn()`], polled by [`drive_coroutines`] after
+/// they were woken. Lives in the world as a non-send resource.
+#[derive(Default)]
+pub struct Coroutines {
+    running: HashMap<PromiseId, (Coroutine, DiscardFn)>,
+    woken: Arc<Mutex<HashSet<PromiseId>>>,
+    discarded: HashSet<PromiseId>,
+}
+
+impl Coroutines {
+    fn get(world: &mut World) -> Mut<'_, Coroutines> {
+        if !world.contains_non_send::<Coroutines>() {
+            world.insert_non_send_resource(Coroutines::default());
+        }
+        world.non_send_resource_mut::<Coroutines>()
+    }
+    fn insert(&mut self, id: PromiseId, coroutine: Coroutine, discard: DiscardFn) {
+        self.running.insert(id, (coroutine, discard));
+        self.woken.lock().unwrap().insert(id);
+    }
+    fn remove(&mut self, id: PromiseId) -> Option<Coroutine> {
+        let coroutine = self.running.remove(&id).map(|(coroutine, _)| coroutine);
+        if coroutine.is_none() {
+            // discarded while being polled
+            self.discarded.insert(id);
+        }
+        coroutine
+    }
+}
+
+pub fn drive_coroutines(world: &mut World) {
+    let woken = {
+        let coroutines = Coroutines::get(world);
+        let woken = mem::take(&mut *coroutines.woken.lock().unwrap());
+        woken
+    };
+    for id in woken {
+        let mut coroutines = world.non_send_resource_mut::<Coroutines>();
+        let Some((mut coroutine, discard)) = coroutines.running.remove(&id) else {
+            continue;
+        };
+        let waker = Waker::from(Arc::new(CoroutineWaker {
+            id,
+            woken: coroutines.woken.clone(),
+        }));
+        let previous = CURRENT.with(|current| current.replace(Some((id, discard))));
+        let poll = enter(world, || coroutine.0.as_mut().poll(&mut Context::from_waker(&waker)));
+        CURRENT.with(|current| current.set(previous));
+        let mut coroutines = world.non_send_resource_mut::<Coroutines>();
+        if coroutines.discarded.remove(&id) || poll.is_ready() {
+            enter(world, move || drop(coroutine));
+        } else {
+            coroutines.running.insert(id, (coroutine, discard));
+        }
+    }
+}
+
+/// Restores the previous world pointer when dropped.
+struct WorldGuard(*mut World);
+impl Drop for WorldGuard {
+    fn drop(&mut self) {
+        WORLD.with(|w| w.set(self.0));
+    }
+}
+
+/// Makes the `world` available for the futures polled inside `f`.
+fn enter<T, F: FnOnce() -> T>(world: &mut World, f: F) -> T {
+    let _guard = WorldGuard(WORLD.with(|w| w.replace(world as *mut World)));
+    f()
+}
+
+fn try_with_world<T, F: FnOnce(&mut World) -> T>(f: F) -> Option<T> {
+    // the pointer is taken while `f` runs, so nested access can't alias it
+    let world = WORLD.with(|w| w.replace(ptr::null_mut()));
+    let _guard = WorldGuard(world);
+    if world.is_null() {
+        return None;
+    }
+    // SAFETY: the pointer is set by `enter()` from the exclusive `&mut World`
+    // which is not used until `enter()` returns
+    Some(f(unsafe { &mut *world }))
+}
+
+fn with_world<T, F: FnOnce(&mut World) -> T>(f: F) -> T {
+    try_with_world(f).expect("pecs futures should be awaited inside pecs::spawn()")
+}
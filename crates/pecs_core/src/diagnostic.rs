@@ -0,0 +1,505 @@
+//! Introspection of the pending promises and leak/stall detection
+use bevy::{
+    core::FrameCount,
+    utils::{
+        get_short_name,
+        tracing::{field, span::EnteredSpan, Span},
+    },
+};
+use std::{cell::Cell, thread};
+
+use super::*;
+
+/// The chain running its callbacks on this thread
+struct ChainLink {
+    /// Label inherited by the promises registered inside the chain
+    label: Option<String>,
+    /// Origin inherited by the promises registered inside the chain
+    origin: Option<&'static Location<'static>>,
+    /// Queue of the world the chain belongs to, promises dropped inside the chain go there
+    dropped: Option<DroppedPromises>,
+}
+thread_local!(static CHAIN: RefCell<Vec<ChainLink>> = const { RefCell::new(vec![]) });
+thread_local!(static QUIET: Cell<bool> = const { Cell::new(false) });
+
+/// Enables the debug warnings about promises that never resolve. Insert it
+/// as a resource to opt in:
+/// ```ignore
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(PecsPlugin)
+///     .insert_resource(PromiseDebug {
+///         stall_threshold: 5.,
+///         ..default()
+///     })
+///     .run();
+/// ```
+/// Warnings include the chain label set with [`Promise::named()`] or the
+/// location the chain was created at. Insert the resource before the app starts,
+/// so `PecsPlugin` enables the [`PendingPromises`] tracking the warnings rely on.
+#[derive(Resource, Clone, Debug)]
+pub struct PromiseDebug {
+    /// Warn once about a promise waiting for a timer, button, task, etc.
+    /// longer than this amount of seconds
+    pub stall_threshold: f32,
+    /// Warn when the [`Promise`] is dropped without being registered or awaited
+    pub warn_dropped: bool,
+    /// Warn when [`promise_resolve()`] is called for the promise which is not registered
+    pub warn_unknown: bool,
+}
+
+impl Default for PromiseDebug {
+    fn default() -> Self {
+        PromiseDebug {
+            stall_threshold: 10.,
+            warn_dropped: true,
+            warn_unknown: true,
+        }
+    }
+}
+
+/// What the pending promise is waiting for
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PromiseAwaits {
+    /// Composite promise created with [`Promise::new()`], [`then()`][PromiseLikeBase::then] or
+    /// [`map()`][PromiseLikeBase::map] waiting for the nested promise
+    Chain,
+    /// [`timeout()`][crate::timer::timeout]
+    Timer,
+    /// Button interaction from [`ui`][crate::ui]
+    Button,
+    /// `http` request
+    Http,
+    /// Background [`task`][crate::task]
+    Task,
+    /// State transition from [`state::asyn::set()`][crate::state::asyn::set]
+    State,
+    /// [`coroutine::spawn()`][crate::coroutine::spawn] future
+    Coroutine,
+    /// Next value of the [`PromiseStream`][crate::stream::PromiseStream]
+    Stream,
+    /// [`Sender::send()`][crate::channel::Sender::send] or [`Receiver::recv()`][crate::channel::Receiver::recv]
+    /// waiting for the channel
+    Channel,
+    /// Next event from [`event::asyn::next()`][crate::event::asyn::next]
+    Event,
+    /// Component watched by [`component::asyn`][crate::component::asyn] promises
+    Component,
+    /// Condition from [`until()`][crate::condition::until] or [`until_some()`][crate::condition::until_some]
+    Condition,
+    /// Frame wait from [`frame`][crate::frame] module
+    Frame,
+    /// Result queued for the schedule with [`in_schedule()`][Promise::in_schedule]
+    Schedule,
+    /// Custom promise created with [`Promise::register()`], holds the type name of
+    /// the `on_invoke` callback by default
+    Custom(&'static str),
+}
+
+impl std::fmt::Display for PromiseAwaits {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PromiseAwaits::Chain => write!(f, "chain"),
+            PromiseAwaits::Timer => write!(f, "timer"),
+            PromiseAwaits::Button => write!(f, "button"),
+            PromiseAwaits::Http => write!(f, "http"),
+            PromiseAwaits::Task => write!(f, "task"),
+            PromiseAwaits::State => write!(f, "state"),
+            PromiseAwaits::Coroutine => write!(f, "coroutine"),
+            PromiseAwaits::Stream => write!(f, "stream"),
+            PromiseAwaits::Channel => write!(f, "channel"),
+            PromiseAwaits::Event => write!(f, "event"),
+            PromiseAwaits::Component => write!(f, "component"),
+            PromiseAwaits::Condition => write!(f, "condition"),
+            PromiseAwaits::Frame => write!(f, "frame"),
+            PromiseAwaits::Schedule => write!(f, "schedule"),
+            PromiseAwaits::Custom(name) => write!(f, "{}", get_short_name(name)),
+        }
+    }
+}
+
+/// Registered promise waiting to be resolved
+#[derive(Clone, Debug)]
+pub struct PendingPromise {
+    pub id: PromiseId,
+    /// Type name of the promise state
+    pub state: &'static str,
+    /// Type name of the promise result
+    pub result: &'static str,
+    /// Label set with [`Promise::named()`] on this promise or on the chain it belongs to
+    pub label: Option<String>,
+    pub awaits: PromiseAwaits,
+    /// [`Time::elapsed_seconds()`] at the moment of registration
+    pub registered_at: f32,
+    /// [`FrameCount`] at the moment of registration
+    pub registered_frame: u32,
+    /// Location the promise was created at
+    pub location: &'static Location<'static>,
+    /// Location the outermost promise of the chain was created at
+    pub origin: &'static Location<'static>,
+    /// `promise` tracing span, the parent of the spans of the chained steps
+    /// and nested promises
+    pub span: Span,
+    order: u64,
+    stalled: bool,
+}
+
+impl PendingPromise {
+    /// Seconds passed since the promise was registered
+    pub fn age(&self, time: &Time) -> f32 {
+        time.elapsed_seconds() - self.registered_at
+    }
+    /// Frames passed since the promise was registered
+    pub fn age_frames(&self, frame: &FrameCount) -> u32 {
+        frame.0.wrapping_sub(self.registered_frame)
+    }
+    /// `true` once the promise is reported by [`detect_stalled_promises`]
+    pub fn is_stalled(&self) -> bool {
+        self.stalled
+    }
+}
+
+impl std::fmt::Display for PendingPromise {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}<{}, {}> awaits {}",
+            self.id,
+            get_short_name(self.state),
+            get_short_name(self.result),
+            self.awaits
+        )?;
+        if let Some(label) = &self.label {
+            write!(f, " ({label})")
+        } else {
+            write!(f, " (created at {})", self.origin)
+        }
+    }
+}
+
+/// All registered promises across the state/result types. Tracking costs a tracing span and
+/// a map entry per promise, so it is opt-in: promises are tracked while the resource exists.
+/// `PecsPlugin` adds it when [`PromiseDebug`] is inserted before the app starts,
+/// [`PendingPromisesOverlayPlugin`] always adds it, otherwise init it manually:
+/// ```ignore
+/// app.init_resource::<PendingPromises>();
+/// ```
+/// ```ignore
+/// fn log_pending(pending: Res<PendingPromises>, time: Res<Time>) {
+///     for promise in pending.iter() {
+///         info!("{promise} for {:.1}s", promise.age(&time));
+///     }
+/// }
+/// ```
+#[derive(Resource, Default)]
+pub struct PendingPromises {
+    promises: HashMap<PromiseId, PendingPromise>,
+    registered: u64,
+}
+
+impl PendingPromises {
+    pub fn get(&self, id: PromiseId) -> Option<&PendingPromise> {
+        self.promises.get(&id)
+    }
+    /// Iterates over pending promises in the order of registration
+    pub fn iter(&self) -> impl Iterator<Item = &PendingPromise> {
+        let mut pending: Vec<_> = self.promises.values().collect();
+        pending.sort_by_key(|promise| promise.order);
+        pending.into_iter()
+    }
+    pub fn len(&self) -> usize {
+        self.promises.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.promises.is_empty()
+    }
+}
+
+impl<S: 'static + Send, R: 'static + Send> Promise<S, R> {
+    /// Sets the `label` shown by [`PendingPromises`] and recorded in the `promise`
+    /// tracing span for this promise and the promises chained with it.
+    /// ```ignore
+    /// commands.add(
+    ///     asyn::timeout(1.)
+    ///         .then(asyn!(_ => info!("Level loaded")))
+    ///         .named("load level"),
+    /// );
+    /// ```
+    pub fn named<L: Into<String>>(mut self, label: L) -> Promise<S, R> {
+        self.label = Some(label.into());
+        self
+    }
+    /// Describes what the promise is waiting for. Custom promises created with
+    /// [`Promise::register()`] may use it to be listed nicely in [`PendingPromises`].
+    pub fn awaits(mut self, awaits: PromiseAwaits) -> Promise<S, R> {
+        self.awaits = awaits;
+        self
+    }
+}
+
+impl<'w, 's, 'a, S: 'static + Send, R: 'static + Send> PromiseChain<'w, 's, 'a, S, R> {
+    /// Sets the chain label, see [`Promise::named()`]
+    pub fn named<L: Into<String>>(mut self, label: L) -> Self {
+        self.promise = self.promise.take().map(|p| p.named(label));
+        self
+    }
+}
+
+pub(crate) fn track<S: 'static + Send, R: 'static + Send>(world: &mut World, promise: &Promise<S, R>) {
+    let registered_at = world.get_resource::<Time>().map(|t| t.elapsed_seconds()).unwrap_or(0.);
+    let registered_frame = world.get_resource::<FrameCount>().map(|f| f.0).unwrap_or(0);
+    let Some(mut pending) = world.get_resource_mut::<PendingPromises>() else {
+        return;
+    };
+    // nested promises inherit the label and the origin of the chain
+    let (chain_label, origin) = CHAIN.with(|chain| {
+        chain
+            .borrow()
+            .last()
+            .map_or((None, None), |link| (link.label.clone(), link.origin))
+    });
+    let origin = origin.unwrap_or(promise.location);
+    let span = info_span!("promise", id = %promise.id, label = field::Empty, awaits = %promise.awaits);
+    let label = promise.label.clone().or(chain_label);
+    if let Some(label) = &label {
+        span.record("label", label.as_str());
+    }
+    pending.registered += 1;
+    let order = pending.registered;
+    pending.promises.insert(
+        promise.id,
+        PendingPromise {
+            id: promise.id,
+            state: type_name::<S>(),
+            result: type_name::<R>(),
+            label,
+            awaits: promise.awaits,
+            registered_at,
+            registered_frame,
+            location: promise.location,
+            origin,
+            span,
+            order,
+            stalled: false,
+        },
+    );
+}
+
+pub(crate) fn untrack(world: &mut World, id: PromiseId, resolved: bool) {
+    let Some(promise) = world
+        .get_resource_mut::<PendingPromises>()
+        .and_then(|mut pending| pending.promises.remove(&id))
+    else {
+        return;
+    };
+    let outcome = if resolved { "resolved" } else { "discarded" };
+    trace!(parent: &promise.span, "{id} {outcome}");
+}
+
+/// Leaves the chain entered with [`enter_chain()`] when dropped
+pub(crate) struct ChainGuard {
+    entered: bool,
+    _span: Option<EnteredSpan>,
+}
+impl Drop for ChainGuard {
+    fn drop(&mut self) {
+        if self.entered {
+            CHAIN.with(|chain| chain.borrow_mut().pop());
+        }
+    }
+}
+
+/// Makes the promises registered or dropped until the guard is dropped belong to the chain
+/// of the `id` promise and enters the span of the promise.
+pub(crate) fn enter_chain(world: &World, id: PromiseId) -> ChainGuard {
+    let promise = world.get_resource::<PendingPromises>().and_then(|p| p.get(id));
+    let dropped = world.get_resource::<DroppedPromises>().cloned();
+    if promise.is_none() && dropped.is_none() {
+        return ChainGuard {
+            entered: false,
+            _span: None,
+        };
+    }
+    let link = ChainLink {
+        label: promise.and_then(|promise| promise.label.clone()),
+        origin: promise.map(|promise| promise.origin),
+        dropped,
+    };
+    CHAIN.with(|chain| chain.borrow_mut().push(link));
+    ChainGuard {
+        entered: true,
+        _span: promise.map(|promise| promise.span.clone().entered()),
+    }
+}
+
+/// Drops the `value` without warning about the promises it holds.
+pub(crate) fn drop_quietly<T>(value: T) {
+    let quiet = QUIET.with(|quiet| quiet.replace(true));
+    drop(value);
+    QUIET.with(|q| q.set(quiet));
+}
+
+pub(crate) fn unregistered_drop(id: PromiseId, label: Option<&str>, location: &'static Location<'static>) {
+    if QUIET.with(|quiet| quiet.get()) || thread::panicking() {
+        return;
+    }
+    CHAIN.with(|chain| {
+        let chain = chain.borrow();
+        let Some(link) = chain.last() else {
+            return;
+        };
+        if let Some(dropped) = &link.dropped {
+            dropped.0.lock().unwrap().push(DroppedPromise {
+                id,
+                label: label.map(str::to_string).or_else(|| link.label.clone()),
+                location,
+            });
+        }
+    });
+}
+
+/// Unregistered promise dropped inside the chain, see [`DroppedPromises`]
+#[derive(Clone, Debug)]
+pub struct DroppedPromise {
+    pub id: PromiseId,
+    /// Label of the promise or the chain it was dropped in
+    pub label: Option<String>,
+    /// Location the promise was created at
+    pub location: &'static Location<'static>,
+}
+
+/// Promises dropped without being registered or awaited since the last [`warn_dropped_promises`]
+/// run. The resource exists only while [`PromiseDebug::warn_dropped`] is set. A promise is dropped
+/// without access to the world, so only the promises dropped while the world runs chain callbacks
+/// (`asyn!` bodies, `register`/`resolve` callbacks) are recorded.
+#[derive(Resource, Clone, Default)]
+pub struct DroppedPromises(Arc<Mutex<Vec<DroppedPromise>>>);
+
+impl DroppedPromises {
+    pub fn len(&self) -> usize {
+        self.0.lock().unwrap().len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.lock().unwrap().is_empty()
+    }
+}
+
+/// Warns about the promises recorded in [`DroppedPromises`] and adds or removes the resource
+/// when [`PromiseDebug::warn_dropped`] changes.
+pub fn warn_dropped_promises(
+    mut commands: Commands,
+    debug: Option<Res<PromiseDebug>>,
+    dropped: Option<Res<DroppedPromises>>,
+) {
+    let enabled = debug.is_some_and(|debug| debug.warn_dropped);
+    let Some(dropped) = dropped else {
+        if enabled {
+            commands.init_resource::<DroppedPromises>();
+        }
+        return;
+    };
+    if !enabled {
+        commands.remove_resource::<DroppedPromises>();
+        return;
+    }
+    let dropped = mem::take(&mut *dropped.0.lock().unwrap());
+    for DroppedPromise { id, label, location } in dropped {
+        match label {
+            Some(label) => warn!("{id} ({label}) created at {location} dropped without being registered or awaited"),
+            None => warn!("{id} created at {location} dropped without being registered or awaited"),
+        }
+    }
+}
+
+pub(crate) fn unknown_resolve<S: 'static + Send, R: 'static + Send>(
+    world: &World,
+    id: PromiseId,
+    location: &'static Location<'static>,
+) {
+    if world.get_resource::<PromiseDebug>().is_some_and(|d| d.warn_unknown) {
+        warn!(
+            "Resolving unknown {id}<{}, {}> at {location}: it was never registered or already resolved or discarded",
+            type_name::<S>(),
+            type_name::<R>(),
+        );
+    }
+}
+
+/// Warns once about the promises waiting longer than [`PromiseDebug::stall_threshold`].
+/// Composite promises are skipped, the promise they wait for is reported instead.
+pub fn detect_stalled_promises(
+    debug: Option<Res<PromiseDebug>>,
+    pending: Option<ResMut<PendingPromises>>,
+    time: Res<Time>,
+) {
+    let (Some(debug), Some(mut pending)) = (debug, pending) else {
+        return;
+    };
+    let mut stalled: Vec<_> = pending
+        .promises
+        .values_mut()
+        .filter(|promise| {
+            promise.awaits != PromiseAwaits::Chain && !promise.stalled && promise.age(&time) >= debug.stall_threshold
+        })
+        .collect();
+    stalled.sort_by_key(|promise| promise.order);
+    for promise in stalled {
+        promise.stalled = true;
+        warn!("{promise} is pending for {:.1}s", promise.age(&time));
+    }
+}
+
+/// Renders the [`PendingPromises`] list over the game with `bevy_ui`
+pub struct PendingPromisesOverlayPlugin;
+impl Plugin for PendingPromisesOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingPromises>();
+        app.add_systems(Startup, spawn_pending_promises_overlay);
+        app.add_systems(Update, update_pending_promises_overlay);
+    }
+}
+
+/// Text node of the [`PendingPromisesOverlayPlugin`]
+#[derive(Component)]
+pub struct PendingPromisesOverlay;
+
+fn spawn_pending_promises_overlay(mut commands: Commands) {
+    commands.spawn((
+        PendingPromisesOverlay,
+        TextBundle::from_section(
+            "",
+            TextStyle {
+                font_size: 14.,
+                color: Color::WHITE,
+                ..default()
+            },
+        )
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            left: Val::Px(4.),
+            top: Val::Px(4.),
+            ..default()
+        })
+        .with_background_color(Color::rgba(0., 0., 0., 0.6)),
+        ZIndex::Global(i32::MAX),
+    ));
+}
+
+fn update_pending_promises_overlay(
+    pending: Res<PendingPromises>,
+    time: Res<Time>,
+    frame: Res<FrameCount>,
+    mut overlay: Query<&mut Text, With<PendingPromisesOverlay>>,
+) {
+    let mut text = format!("Pending promises: {}", pending.len());
+    for promise in pending.iter() {
+        text.push_str(&format!(
+            "\n{promise} for {:.1}s/{}f",
+            promise.age(&time),
+            promise.age_frames(&frame)
+        ));
+    }
+    for mut overlay in overlay.iter_mut() {
+        overlay.sections[0].value = text.clone();
+    }
+}
@@ -0,0 +1,131 @@
+//! Promises resolving with the next Bevy [`Event`]
+//! ```ignore
+//! #[derive(Event, Clone)]
+//! struct Damage { target: Entity, amount: f32 }
+//!
+//! fn setup(mut commands: Commands, boss: Query<Entity, With<Boss>>) {
+//!     let boss = boss.single();
+//!     commands.add(
+//!         asyn::event::next_matching(move |d: &Damage| d.target == boss)
+//!             .then(asyn!(_, damage => info!("Boss took {} damage", damage.amount))),
+//!     );
+//! }
+//! ```
+use bevy::ecs::event::ManualEventReader;
+
+use super::*;
+
+pub mod asyn {
+    use super::{register_waiter, Promise};
+    use bevy::prelude::*;
+
+    /// Creates promise that resolves with the next `E` event sent after the promise is registered.
+    /// The event should be added with [`App::add_event()`] before.
+    #[track_caller]
+    pub fn next<E: Event + Clone>() -> Promise<(), E> {
+        register_waiter::<E>(None)
+    }
+
+    /// Creates promise that resolves with the next `E` event `predicate` returns `true` for
+    #[track_caller]
+    pub fn next_matching<E: Event + Clone>(predicate: impl 'static + Fn(&E) -> bool + Send + Sync) -> Promise<(), E> {
+        register_waiter::<E>(Some(Box::new(predicate)))
+    }
+}
+
+type EventPredicate<E> = Box<dyn Fn(&E) -> bool + Send + Sync>;
+
+struct EventWaiter<E: Event> {
+    reader: ManualEventReader<E>,
+    predicate: Option<EventPredicate<E>>,
+}
+
+/// Promises waiting for the `E` event, each one with its own reader.
+#[derive(Resource)]
+pub struct EventWaiters<E: Event>(HashMap<PromiseId, EventWaiter<E>>);
+impl<E: Event> Default for EventWaiters<E> {
+    fn default() -> Self {
+        EventWaiters(HashMap::new())
+    }
+}
+impl<E: Event> EventWaiters<E> {
+    /// Number of promises waiting for the event
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+#[track_caller]
+fn register_waiter<E: Event + Clone>(predicate: Option<EventPredicate<E>>) -> Promise<(), E> {
+    Promise::register(
+        move |world, id| {
+            if !world.contains_resource::<EventWaiters<E>>() {
+                world.init_resource::<EventWaiters<E>>();
+                PromiseResolvers::add(world, resolve_waiters::<E>);
+            }
+            let reader = world
+                .get_resource::<Events<E>>()
+                .unwrap_or_else(|| panic!("{} should be added with App::add_event()", type_name::<E>()))
+                .get_reader_current();
+            world
+                .resource_mut::<EventWaiters<E>>()
+                .0
+                .insert(id, EventWaiter { reader, predicate });
+        },
+        move |world, id| {
+            if let Some(mut waiters) = world.get_resource_mut::<EventWaiters<E>>() {
+                waiters.0.remove(&id);
+            }
+        },
+    )
+    .awaits(PromiseAwaits::Event)
+}
+
+fn resolve_waiters<E: Event + Clone>(
+    mut commands: Commands,
+    events: Res<Events<E>>,
+    mut waiters: ResMut<EventWaiters<E>>,
+) {
+    waiters.0.retain(|id, EventWaiter { reader, predicate }| {
+        let next = reader.read(&events).find(|event| match predicate {
+            Some(predicate) => predicate(event),
+            None => true,
+        });
+        match next {
+            Some(event) => {
+                commands.add(PromiseCommand::resolve(*id, event.clone()));
+                false
+            }
+            None => true,
+        }
+    });
+}
+
+pub struct StatefulAsynEvents<S>(S);
+impl<S: 'static + Send> StatefulAsynEvents<S> {
+    /// Stateful version of [`asyn::next()`]
+    #[track_caller]
+    pub fn next<E: Event + Clone>(self) -> Promise<S, E> {
+        asyn::next().with(self.0)
+    }
+    /// Stateful version of [`asyn::next_matching()`]
+    #[track_caller]
+    pub fn next_matching<E: Event + Clone>(
+        self,
+        predicate: impl 'static + Fn(&E) -> bool + Send + Sync,
+    ) -> Promise<S, E> {
+        asyn::next_matching(predicate).with(self.0)
+    }
+}
+
+pub trait EventOpsExtension<S> {
+    fn events(self) -> StatefulAsynEvents<S>;
+}
+impl<S: 'static + Send> EventOpsExtension<S> for AsynOps<S> {
+    fn events(self) -> StatefulAsynEvents<S> {
+        StatefulAsynEvents(self.0)
+    }
+}
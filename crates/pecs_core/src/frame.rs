@@ -0,0 +1,155 @@
+//! Defers promise resolving by frames instead of seconds
+//!
+//! Waits are counted with the frame counter, so step-by-step sequences
+//! behave the same regardless of the frame rate:
+//! ```ignore
+//! fn setup(mut commands: Commands) {
+//!     commands.add(
+//!         Promise::start(asyn!(_ => info!("Spawning wave")))
+//!             .then(asyn!(_ => asyn::frames(3)))
+//!             .then(asyn!(_ => asyn::next_fixed_update()))
+//!             .then(asyn!(_ => info!("Physics ran at least once"))),
+//!     );
+//! }
+//! ```
+use std::collections::{BTreeMap, BTreeSet};
+
+use super::*;
+
+/// Creates promise that resolves in [`First`] of the next frame
+#[track_caller]
+pub fn next_frame() -> Promise<(), ()> {
+    frames(1)
+}
+
+/// Creates promise that resolves in [`First`] after `count` frames passed.
+/// Resolves right away if `count` is zero.
+#[track_caller]
+pub fn frames(count: u32) -> Promise<(), ()> {
+    Promise::<(), ()>::register(
+        move |world, id| {
+            if count == 0 {
+                promise_resolve::<(), ()>(world, id, (), ());
+                return;
+            }
+            let mut frames = world.resource_mut::<Frames>();
+            let at = frames.frame + count as u64;
+            frames.frames.insert(id, at);
+        },
+        move |world, id| {
+            world.resource_mut::<Frames>().frames.remove(&id);
+        },
+    )
+    .awaits(PromiseAwaits::Frame)
+}
+
+/// Creates promise that resolves in the next [`FixedUpdate`] run
+#[track_caller]
+pub fn next_fixed_update() -> Promise<(), ()> {
+    Promise::<(), ()>::register(
+        move |world, id| {
+            world.resource_mut::<Frames>().fixed.insert(id);
+        },
+        move |world, id| {
+            world.resource_mut::<Frames>().fixed.remove(&id);
+        },
+    )
+    .awaits(PromiseAwaits::Frame)
+}
+
+/// Creates promise that resolves in [`Last`] of the current frame
+#[track_caller]
+pub fn end_of_frame() -> Promise<(), ()> {
+    Promise::<(), ()>::register(
+        move |world, id| {
+            world.resource_mut::<Frames>().last.insert(id);
+        },
+        move |world, id| {
+            world.resource_mut::<Frames>().last.remove(&id);
+        },
+    )
+    .awaits(PromiseAwaits::Frame)
+}
+
+pub trait FrameOpsExtension<S> {
+    fn next_frame(self) -> Promise<S, ()>;
+    fn frames(self, count: u32) -> Promise<S, ()>;
+    fn next_fixed_update(self) -> Promise<S, ()>;
+    fn end_of_frame(self) -> Promise<S, ()>;
+}
+impl<S: 'static + Send> FrameOpsExtension<S> for AsynOps<S> {
+    #[track_caller]
+    fn next_frame(self) -> Promise<S, ()> {
+        next_frame().with(self.0)
+    }
+    #[track_caller]
+    fn frames(self, count: u32) -> Promise<S, ()> {
+        frames(count).with(self.0)
+    }
+    #[track_caller]
+    fn next_fixed_update(self) -> Promise<S, ()> {
+        next_fixed_update().with(self.0)
+    }
+    #[track_caller]
+    fn end_of_frame(self) -> Promise<S, ()> {
+        end_of_frame().with(self.0)
+    }
+}
+
+/// Frame counter and the promises waiting for frames, resolved in the order they were registered.
+#[derive(Resource, Default)]
+pub struct Frames {
+    frame: u64,
+    frames: BTreeMap<PromiseId, u64>,
+    fixed: BTreeSet<PromiseId>,
+    last: BTreeSet<PromiseId>,
+}
+
+impl Frames {
+    /// Number of frames started since the plugin was added
+    pub fn frame(&self) -> u64 {
+        self.frame
+    }
+    /// Number of promises waiting for frames
+    pub fn len(&self) -> usize {
+        self.frames.len() + self.fixed.len() + self.last.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+pub fn process_frames(mut commands: Commands, mut frames: ResMut<Frames>) {
+    frames.frame += 1;
+    let frame = frames.frame;
+    frames.frames.retain(|promise, at| {
+        if frame >= *at {
+            commands.add(PromiseCommand::resolve(*promise, ()));
+            false
+        } else {
+            true
+        }
+    });
+}
+
+pub fn process_fixed_updates(mut commands: Commands, mut frames: ResMut<Frames>) {
+    for promise in mem::take(&mut frames.fixed) {
+        commands.add(PromiseCommand::resolve(promise, ()));
+    }
+}
+
+pub fn process_end_of_frame(mut commands: Commands, mut frames: ResMut<Frames>) {
+    for promise in mem::take(&mut frames.last) {
+        commands.add(PromiseCommand::resolve(promise, ()));
+    }
+}
+
+pub struct PromiseFramePlugin;
+impl Plugin for PromiseFramePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Frames>();
+        app.add_systems(First, process_frames);
+        app.add_systems(FixedUpdate, process_fixed_updates);
+        app.add_systems(Last, process_end_of_frame);
+    }
+}
@@ -1,16 +1,29 @@
 use crate::*;
 
-impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
-    type Promise<S2: 'static, R2: 'static> = Promise<S2, R2>;
-    fn then<S2: 'static, R2: 'static>(mut self, func: Asyn![S, R => S2, R2]) -> Promise<S2, R2> {
+impl<S: 'static + Send, R: 'static + Send> Promise<S, R> {
+    /// Schedules `next` invocation after the promise resolves. The result
+    /// of `next` resolves or awaits the returned promise.
+    #[track_caller]
+    pub(crate) fn chain<
+        S2: 'static + Send,
+        R2: 'static + Send,
+        F: 'static + Send + FnOnce(&mut World, PromiseId, S, R) -> PromiseResult<S2, R2>,
+    >(
+        mut self,
+        next: F,
+    ) -> Promise<S2, R2> {
         let id = PromiseId::new();
-        let discard = mem::take(&mut self.discard);
-        let self_id = self.id;
-        self.discard = Some(Box::new(move |world, _id| {
-            promise_discard::<S2, R2>(world, id);
-        }));
+        let pending = PendingLink::new();
+        pending.set::<S, R>(self.id);
+        let discard = pending.clone();
+        let scopes = mem::take(&mut self.scopes);
+        let label = self.label.clone();
+        let time_scale = self.time_scale;
+        let location = Location::caller();
+        self.propagate_discard::<S2, R2>(id);
         self.resolve = Some(Box::new(move |world, state, result| {
-            let pr = func.run((PromiseState::new(state), result), world).into();
+            let _step = info_span!("then", promise = %id, at = %location).entered();
+            let pr = next(world, id, state, result);
             match pr {
                 PromiseResult::Resolve(s, r) => promise_resolve::<S2, R2>(world, id, s, r),
                 PromiseResult::Await(mut p) => {
@@ -26,6 +39,8 @@ impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
                     p.resolve = Some(Box::new(move |world, s, r| {
                         promise_resolve::<S2, R2>(world, id, s, r);
                     }));
+                    pending.set::<S2, R2>(p.id);
+                    p.propagate_discard::<S2, R2>(id);
                     promise_register::<S2, R2>(world, p);
                 }
             }
@@ -35,22 +50,33 @@ impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
             register: Some(Box::new(move |world, _id| {
                 promise_register::<S, R>(world, self);
             })),
-            discard: Some(Box::new(move |world, _id| {
-                if let Some(discard) = discard {
-                    discard(world, self_id);
-                }
-            })),
+            discard: Some(Box::new(move |world, _id| discard.discard(world))),
             resolve: None,
+            handle: None,
+            scopes,
+            label,
+            time_scale,
+            awaits: PromiseAwaits::Chain,
+            location,
         }
     }
+}
+
+impl<S: 'static + Send, R: 'static + Send> PromiseLikeBase<S, R> for Promise<S, R> {
+    type Promise<S2: 'static + Send, R2: 'static + Send> = Promise<S2, R2>;
+    #[track_caller]
+    fn then<S2: 'static + Send, R2: 'static + Send>(self, func: Asyn![S, R => S2, R2]) -> Promise<S2, R2> {
+        self.chain(move |world, _id, state, result| func.run((PromiseState::new(state), result), world).into())
+    }
 
-    fn map_result<R2: 'static, F: 'static + FnOnce(R) -> R2>(mut self, map: F) -> Self::Promise<S, R2> {
+    fn map_result<R2: 'static + Send, F: 'static + Send + FnOnce(R) -> R2>(mut self, map: F) -> Self::Promise<S, R2> {
         let id = PromiseId::new();
-        let discard = mem::take(&mut self.discard);
         let self_id = self.id;
-        self.discard = Some(Box::new(move |world, _id| {
-            promise_discard::<S, R2>(world, id);
-        }));
+        let scopes = mem::take(&mut self.scopes);
+        let label = self.label.clone();
+        let time_scale = self.time_scale;
+        let location = self.location;
+        self.propagate_discard::<S, R2>(id);
         self.resolve = Some(Box::new(move |world, state, result| {
             let result = map(result);
             promise_resolve::<S, R2>(world, id, state, result);
@@ -61,23 +87,28 @@ impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
                 promise_register::<S, R>(world, self);
             })),
             discard: Some(Box::new(move |world, _id| {
-                if let Some(discard) = discard {
-                    discard(world, self_id);
-                }
+                promise_discard::<S, R>(world, self_id);
             })),
             resolve: None,
+            handle: None,
+            scopes,
+            label,
+            time_scale,
+            awaits: PromiseAwaits::Chain,
+            location,
         }
     }
-    fn with_result<R2: 'static>(self, value: R2) -> Self::Promise<S, R2> {
+    fn with_result<R2: 'static + Send>(self, value: R2) -> Self::Promise<S, R2> {
         self.map_result(|_| value)
     }
-    fn map<S2: 'static, F: 'static + FnOnce(S) -> S2>(mut self, map: F) -> Self::Promise<S2, R> {
+    fn map<S2: 'static + Send, F: 'static + Send + FnOnce(S) -> S2>(mut self, map: F) -> Self::Promise<S2, R> {
         let id = PromiseId::new();
-        let discard = mem::take(&mut self.discard);
         let self_id = self.id;
-        self.discard = Some(Box::new(move |world, _id| {
-            promise_discard::<S2, R>(world, id);
-        }));
+        let scopes = mem::take(&mut self.scopes);
+        let label = self.label.clone();
+        let time_scale = self.time_scale;
+        let location = self.location;
+        self.propagate_discard::<S2, R>(id);
         self.resolve = Some(Box::new(move |world, state, result| {
             let state = map(state);
             promise_resolve::<S2, R>(world, id, state, result);
@@ -88,32 +119,55 @@ impl<S: 'static, R: 'static> PromiseLikeBase<S, R> for Promise<S, R> {
                 promise_register::<S, R>(world, self);
             })),
             discard: Some(Box::new(move |world, _id| {
-                if let Some(discard) = discard {
-                    discard(world, self_id);
-                }
+                promise_discard::<S, R>(world, self_id);
             })),
             resolve: None,
+            handle: None,
+            scopes,
+            label,
+            time_scale,
+            awaits: PromiseAwaits::Chain,
+            location,
         }
     }
-    fn with<S2: 'static>(self, state: S2) -> Self::Promise<S2, R> {
+    fn with<S2: 'static + Send>(self, state: S2) -> Self::Promise<S2, R> {
         self.map(|_| state)
     }
 }
-impl<S: 'static> PromiseLike<S> for Promise<S, ()> {
-    fn then_repeat<R2: 'static>(self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2> {
+
+impl<R: 'static + Send> PromiseLikeTimeout<R> for Promise<(), R> {
+    #[track_caller]
+    fn timeout(self, duration: impl IntoDuration) -> Promise<(), Result<R, Elapsed>> {
+        let label = self.label.clone();
+        let time_scale = self.time_scale;
+        // `any` discards the promise that lost the race, so timers and tasks are cleaned up
+        let mut promise = Promise::any((self, timer::timeout(duration)))
+            .chain(|_world, _id, _, (inner, _)| PromiseResult::Resolve((), inner.ok_or(Elapsed)));
+        promise.label = label;
+        promise.time_scale = time_scale;
+        promise
+    }
+    #[track_caller]
+    fn timeout_or(self, duration: impl IntoDuration, default: R) -> Promise<(), R> {
+        self.timeout(duration)
+            .map_result(move |result| result.unwrap_or(default))
+    }
+}
+impl<S: 'static + Send> PromiseLike<S> for Promise<S, ()> {
+    fn then_repeat<R2: 'static + Send>(self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2> {
         self.map(|state| (state, func)).then(asyn!(s, _ => {
             let (state, func) = s.value;
             Promise::repeat(state, func)
         }))
     }
-    fn all<A: 'static + AllPromises>(self, all: A) -> Self::Promise<S, A::Result> {
+    fn all<A: 'static + Send + AllPromises>(self, all: A) -> Self::Promise<S, A::Result> {
         self.map(|s| (s, all)).then(asyn!(state => {
             let (state, all) = state.value;
             all.register().with(state)
         }))
     }
 
-    fn any<A: 'static + AnyPromises>(self, any: A) -> Self::Promise<S, A::Result> {
+    fn any<A: 'static + Send + AnyPromises>(self, any: A) -> Self::Promise<S, A::Result> {
         self.map(|s| (s, any)).then(asyn!(state => {
             let (state, any) = state.value;
             any.register().with(state)
@@ -121,9 +175,10 @@ impl<S: 'static> PromiseLike<S> for Promise<S, ()> {
     }
 }
 
-impl<'w, 's, 'a, S: 'static, F: FnOnce() -> S> PromiseLikeBase<S, ()> for PromiseCommands<'w, 's, 'a, F> {
-    type Promise<S2: 'static, R2: 'static> = PromiseChain<'w, 's, 'a, S2, R2>;
-    fn then<S2: 'static, R2: 'static>(mut self, func: Asyn![S => S2, R2]) -> Self::Promise<S2, R2> {
+impl<'w, 's, 'a, S: 'static + Send, F: FnOnce() -> S> PromiseLikeBase<S, ()> for PromiseCommands<'w, 's, 'a, F> {
+    type Promise<S2: 'static + Send, R2: 'static + Send> = PromiseChain<'w, 's, 'a, S2, R2>;
+    #[track_caller]
+    fn then<S2: 'static + Send, R2: 'static + Send>(mut self, func: Asyn![S => S2, R2]) -> Self::Promise<S2, R2> {
         let commands = mem::take(&mut self.commands);
         let new_state = mem::take(&mut self.data).unwrap();
         PromiseChain {
@@ -131,7 +186,7 @@ impl<'w, 's, 'a, S: 'static, F: FnOnce() -> S> PromiseLikeBase<S, ()> for Promis
             promise: Some(Promise::new(new_state(), asyn!(s => s)).then(func)),
         }
     }
-    fn map_result<R2: 'static, M: 'static + FnOnce(()) -> R2>(mut self, map: M) -> Self::Promise<S, R2> {
+    fn map_result<R2: 'static + Send, M: 'static + Send + FnOnce(()) -> R2>(mut self, map: M) -> Self::Promise<S, R2> {
         let commands = mem::take(&mut self.commands);
         let new_state = mem::take(&mut self.data).unwrap();
         PromiseChain {
@@ -145,10 +200,10 @@ impl<'w, 's, 'a, S: 'static, F: FnOnce() -> S> PromiseLikeBase<S, ()> for Promis
             )),
         }
     }
-    fn with_result<R2: 'static>(self, value: R2) -> Self::Promise<S, R2> {
+    fn with_result<R2: 'static + Send>(self, value: R2) -> Self::Promise<S, R2> {
         self.map_result(|_| value)
     }
-    fn map<S2: 'static, M: 'static + FnOnce(S) -> S2>(mut self, map: M) -> Self::Promise<S2, ()> {
+    fn map<S2: 'static + Send, M: 'static + Send + FnOnce(S) -> S2>(mut self, map: M) -> Self::Promise<S2, ()> {
         let commands = mem::take(&mut self.commands);
         let new_state = mem::take(&mut self.data).unwrap();
         PromiseChain {
@@ -156,13 +211,13 @@ impl<'w, 's, 'a, S: 'static, F: FnOnce() -> S> PromiseLikeBase<S, ()> for Promis
             promise: Some(Promise::new(map(new_state()), asyn!(s => s))),
         }
     }
-    fn with<S2: 'static>(self, state: S2) -> Self::Promise<S2, ()> {
+    fn with<S2: 'static + Send>(self, state: S2) -> Self::Promise<S2, ()> {
         self.map(|_| state)
     }
 }
 
-impl<'w, 's, 'a, S: 'static, F: FnOnce() -> S> PromiseLike<S> for PromiseCommands<'w, 's, 'a, F> {
-    fn then_repeat<R2: 'static>(mut self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2> {
+impl<'w, 's, 'a, S: 'static + Send, F: FnOnce() -> S> PromiseLike<S> for PromiseCommands<'w, 's, 'a, F> {
+    fn then_repeat<R2: 'static + Send>(mut self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2> {
         let commands = mem::take(&mut self.commands);
         let new_state = mem::take(&mut self.data).unwrap();
         PromiseChain {
@@ -170,7 +225,7 @@ impl<'w, 's, 'a, S: 'static, F: FnOnce() -> S> PromiseLike<S> for PromiseCommand
             promise: Some(Promise::repeat(new_state(), func)),
         }
     }
-    fn all<A: 'static + AllPromises>(mut self, all: A) -> Self::Promise<S, A::Result> {
+    fn all<A: 'static + Send + AllPromises>(mut self, all: A) -> Self::Promise<S, A::Result> {
         let commands = mem::take(&mut self.commands);
         let new_state = mem::take(&mut self.data).unwrap();
         PromiseChain {
@@ -178,7 +233,7 @@ impl<'w, 's, 'a, S: 'static, F: FnOnce() -> S> PromiseLike<S> for PromiseCommand
             promise: Some(Promise::all(all).with(new_state())),
         }
     }
-    fn any<A: 'static + AnyPromises>(mut self, any: A) -> Self::Promise<S, A::Result> {
+    fn any<A: 'static + Send + AnyPromises>(mut self, any: A) -> Self::Promise<S, A::Result> {
         let commands = mem::take(&mut self.commands);
         let new_state = mem::take(&mut self.data).unwrap();
         PromiseChain {
@@ -188,9 +243,12 @@ impl<'w, 's, 'a, S: 'static, F: FnOnce() -> S> PromiseLike<S> for PromiseCommand
     }
 }
 
-impl<'w, 's, 'a, S: 'static, R: 'static> PromiseLikeBase<S, R> for PromiseCommands<'w, 's, 'a, Promise<S, R>> {
-    type Promise<S2: 'static, R2: 'static> = PromiseChain<'w, 's, 'a, S2, R2>;
-    fn then<S2: 'static, R2: 'static>(mut self, func: Asyn![S, R => S2, R2]) -> Self::Promise<S2, R2> {
+impl<'w, 's, 'a, S: 'static + Send, R: 'static + Send> PromiseLikeBase<S, R>
+    for PromiseCommands<'w, 's, 'a, Promise<S, R>>
+{
+    type Promise<S2: 'static + Send, R2: 'static + Send> = PromiseChain<'w, 's, 'a, S2, R2>;
+    #[track_caller]
+    fn then<S2: 'static + Send, R2: 'static + Send>(mut self, func: Asyn![S, R => S2, R2]) -> Self::Promise<S2, R2> {
         let commands = mem::take(&mut self.commands);
         let promise = mem::take(&mut self.data).unwrap();
         PromiseChain {
@@ -198,7 +256,7 @@ impl<'w, 's, 'a, S: 'static, R: 'static> PromiseLikeBase<S, R> for PromiseComman
             promise: Some(promise.then(func)),
         }
     }
-    fn map_result<R2: 'static, F: 'static + FnOnce(R) -> R2>(mut self, map: F) -> Self::Promise<S, R2> {
+    fn map_result<R2: 'static + Send, F: 'static + Send + FnOnce(R) -> R2>(mut self, map: F) -> Self::Promise<S, R2> {
         let commands = mem::take(&mut self.commands);
         let promise = mem::take(&mut self.data).unwrap();
         PromiseChain {
@@ -206,10 +264,10 @@ impl<'w, 's, 'a, S: 'static, R: 'static> PromiseLikeBase<S, R> for PromiseComman
             promise: Some(promise.map_result(map)),
         }
     }
-    fn with_result<R2: 'static>(self, value: R2) -> Self::Promise<S, R2> {
+    fn with_result<R2: 'static + Send>(self, value: R2) -> Self::Promise<S, R2> {
         self.map_result(|_| value)
     }
-    fn map<S2: 'static, F: 'static + FnOnce(S) -> S2>(mut self, m: F) -> Self::Promise<S2, R> {
+    fn map<S2: 'static + Send, F: 'static + Send + FnOnce(S) -> S2>(mut self, m: F) -> Self::Promise<S2, R> {
         let commands = mem::take(&mut self.commands);
         let promise = mem::take(&mut self.data).unwrap();
         PromiseChain {
@@ -217,12 +275,28 @@ impl<'w, 's, 'a, S: 'static, R: 'static> PromiseLikeBase<S, R> for PromiseComman
             promise: Some(promise.map(m)),
         }
     }
-    fn with<S2: 'static>(self, state: S2) -> Self::Promise<S2, R> {
+    fn with<S2: 'static + Send>(self, state: S2) -> Self::Promise<S2, R> {
         self.map(|_| state)
     }
 }
-impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseCommands<'w, 's, 'a, Promise<S, ()>> {
-    fn then_repeat<R2: 'static>(mut self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2> {
+impl<'w, 's, 'a, R: 'static + Send> PromiseLikeTimeout<R> for PromiseCommands<'w, 's, 'a, Promise<(), R>> {
+    #[track_caller]
+    fn timeout(mut self, duration: impl IntoDuration) -> Self::Promise<(), Result<R, Elapsed>> {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.timeout(duration)),
+        }
+    }
+    #[track_caller]
+    fn timeout_or(self, duration: impl IntoDuration, default: R) -> Self::Promise<(), R> {
+        self.timeout(duration)
+            .map_result(move |result| result.unwrap_or(default))
+    }
+}
+impl<'w, 's, 'a, S: 'static + Send> PromiseLike<S> for PromiseCommands<'w, 's, 'a, Promise<S, ()>> {
+    fn then_repeat<R2: 'static + Send>(mut self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2> {
         let commands = mem::take(&mut self.commands);
         let promise = mem::take(&mut self.data).unwrap();
         PromiseChain {
@@ -230,7 +304,7 @@ impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseCommands<'w, 's, 'a, Prom
             promise: Some(promise.then_repeat(func)),
         }
     }
-    fn all<A: 'static + AllPromises>(mut self, all: A) -> Self::Promise<S, A::Result> {
+    fn all<A: 'static + Send + AllPromises>(mut self, all: A) -> Self::Promise<S, A::Result> {
         let commands = mem::take(&mut self.commands);
         let promise = mem::take(&mut self.data).unwrap();
         PromiseChain {
@@ -238,7 +312,7 @@ impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseCommands<'w, 's, 'a, Prom
             promise: Some(promise.all(all)),
         }
     }
-    fn any<A: 'static + AnyPromises>(mut self, any: A) -> Self::Promise<S, A::Result> {
+    fn any<A: 'static + Send + AnyPromises>(mut self, any: A) -> Self::Promise<S, A::Result> {
         let commands = mem::take(&mut self.commands);
         let promise = mem::take(&mut self.data).unwrap();
         PromiseChain {
@@ -248,9 +322,10 @@ impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseCommands<'w, 's, 'a, Prom
     }
 }
 
-impl<'w, 's, 'a, S: 'static, R: 'static> PromiseLikeBase<S, R> for PromiseChain<'w, 's, 'a, S, R> {
-    type Promise<S2: 'static, R2: 'static> = PromiseChain<'w, 's, 'a, S2, R2>;
-    fn then<S2: 'static, R2: 'static>(mut self, func: Asyn![S, R => S2, R2]) -> Self::Promise<S2, R2> {
+impl<'w, 's, 'a, S: 'static + Send, R: 'static + Send> PromiseLikeBase<S, R> for PromiseChain<'w, 's, 'a, S, R> {
+    type Promise<S2: 'static + Send, R2: 'static + Send> = PromiseChain<'w, 's, 'a, S2, R2>;
+    #[track_caller]
+    fn then<S2: 'static + Send, R2: 'static + Send>(mut self, func: Asyn![S, R => S2, R2]) -> Self::Promise<S2, R2> {
         let commands = mem::take(&mut self.commands).unwrap();
         let promise = mem::take(&mut self.promise).unwrap();
         PromiseChain {
@@ -258,7 +333,7 @@ impl<'w, 's, 'a, S: 'static, R: 'static> PromiseLikeBase<S, R> for PromiseChain<
             promise: Some(promise.then(func)),
         }
     }
-    fn map_result<R2: 'static, F: 'static + FnOnce(R) -> R2>(mut self, map: F) -> Self::Promise<S, R2> {
+    fn map_result<R2: 'static + Send, F: 'static + Send + FnOnce(R) -> R2>(mut self, map: F) -> Self::Promise<S, R2> {
         let commands = mem::take(&mut self.commands).unwrap();
         let promise = mem::take(&mut self.promise).unwrap();
         PromiseChain {
@@ -266,10 +341,10 @@ impl<'w, 's, 'a, S: 'static, R: 'static> PromiseLikeBase<S, R> for PromiseChain<
             promise: Some(promise.map_result(map)),
         }
     }
-    fn with_result<R2: 'static>(self, value: R2) -> Self::Promise<S, R2> {
+    fn with_result<R2: 'static + Send>(self, value: R2) -> Self::Promise<S, R2> {
         self.map_result(|_| value)
     }
-    fn map<S2: 'static, F: 'static + FnOnce(S) -> S2>(mut self, map: F) -> Self::Promise<S2, R> {
+    fn map<S2: 'static + Send, F: 'static + Send + FnOnce(S) -> S2>(mut self, map: F) -> Self::Promise<S2, R> {
         let commands = mem::take(&mut self.commands).unwrap();
         let promise = mem::take(&mut self.promise).unwrap();
         PromiseChain {
@@ -277,13 +352,30 @@ impl<'w, 's, 'a, S: 'static, R: 'static> PromiseLikeBase<S, R> for PromiseChain<
             promise: Some(promise.map(map)),
         }
     }
-    fn with<S2: 'static>(self, state: S2) -> Self::Promise<S2, R> {
+    fn with<S2: 'static + Send>(self, state: S2) -> Self::Promise<S2, R> {
         self.map(|_| state)
     }
 }
 
-impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseChain<'w, 's, 'a, S, ()> {
-    fn then_repeat<R2: 'static>(mut self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2> {
+impl<'w, 's, 'a, R: 'static + Send> PromiseLikeTimeout<R> for PromiseChain<'w, 's, 'a, (), R> {
+    #[track_caller]
+    fn timeout(mut self, duration: impl IntoDuration) -> Self::Promise<(), Result<R, Elapsed>> {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.timeout(duration)),
+        }
+    }
+    #[track_caller]
+    fn timeout_or(self, duration: impl IntoDuration, default: R) -> Self::Promise<(), R> {
+        self.timeout(duration)
+            .map_result(move |result| result.unwrap_or(default))
+    }
+}
+
+impl<'w, 's, 'a, S: 'static + Send> PromiseLike<S> for PromiseChain<'w, 's, 'a, S, ()> {
+    fn then_repeat<R2: 'static + Send>(mut self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2> {
         let commands = mem::take(&mut self.commands).unwrap();
         let promise = mem::take(&mut self.promise).unwrap();
         PromiseChain {
@@ -291,7 +383,7 @@ impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseChain<'w, 's, 'a, S, ()>
             promise: Some(promise.then_repeat(func)),
         }
     }
-    fn all<A: 'static + AllPromises>(mut self, all: A) -> Self::Promise<S, A::Result> {
+    fn all<A: 'static + Send + AllPromises>(mut self, all: A) -> Self::Promise<S, A::Result> {
         let commands = mem::take(&mut self.commands).unwrap();
         let promise = mem::take(&mut self.promise).unwrap();
         PromiseChain {
@@ -299,7 +391,7 @@ impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseChain<'w, 's, 'a, S, ()>
             promise: Some(promise.all(all)),
         }
     }
-    fn any<A: 'static + AnyPromises>(mut self, any: A) -> Self::Promise<S, A::Result> {
+    fn any<A: 'static + Send + AnyPromises>(mut self, any: A) -> Self::Promise<S, A::Result> {
         let commands = mem::take(&mut self.commands).unwrap();
         let promise = mem::take(&mut self.promise).unwrap();
         PromiseChain {
@@ -308,3 +400,140 @@ impl<'w, 's, 'a, S: 'static> PromiseLike<S> for PromiseChain<'w, 's, 'a, S, ()>
         }
     }
 }
+
+impl<S: 'static + Send, R: 'static + Send, E: 'static + Send> PromiseLikeResult<S, R, E> for Promise<S, Result<R, E>> {
+    #[track_caller]
+    fn ok_then<S2: 'static + Send, R2: 'static + Send>(
+        self,
+        func: Asyn![S, R => S2, Result<R2, E>],
+    ) -> Self::Promise<S2, Result<R2, E>>
+    where
+        S: Into<S2>,
+    {
+        self.chain(move |world, id, state, result| match result {
+            Ok(value) => panic::convert(world, id, |world| {
+                func.run((PromiseState::new(state), value), world).into()
+            }),
+            Err(err) => PromiseResult::Resolve(state.into(), Err(err)),
+        })
+    }
+    #[track_caller]
+    fn err_then<S2: 'static + Send, E2: 'static + Send>(
+        self,
+        func: Asyn![S, E => S2, Result<R, E2>],
+    ) -> Self::Promise<S2, Result<R, E2>>
+    where
+        S: Into<S2>,
+    {
+        self.chain(move |world, id, state, result| match result {
+            Ok(value) => PromiseResult::Resolve(state.into(), Ok(value)),
+            Err(err) => panic::convert(world, id, |world| {
+                func.run((PromiseState::new(state), err), world).into()
+            }),
+        })
+    }
+    #[track_caller]
+    fn catch<S2: 'static + Send>(self, func: Asyn![S, E => S2, R]) -> Self::Promise<S2, R>
+    where
+        S: Into<S2>,
+    {
+        self.chain(move |world, _id, state, result| match result {
+            Ok(value) => PromiseResult::Resolve(state.into(), value),
+            Err(err) => func.run((PromiseState::new(state), err), world).into(),
+        })
+    }
+}
+
+impl<'w, 's, 'a, S: 'static + Send, R: 'static + Send, E: 'static + Send> PromiseLikeResult<S, R, E>
+    for PromiseCommands<'w, 's, 'a, Promise<S, Result<R, E>>>
+{
+    #[track_caller]
+    fn ok_then<S2: 'static + Send, R2: 'static + Send>(
+        mut self,
+        func: Asyn![S, R => S2, Result<R2, E>],
+    ) -> Self::Promise<S2, Result<R2, E>>
+    where
+        S: Into<S2>,
+    {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.ok_then(func)),
+        }
+    }
+    #[track_caller]
+    fn err_then<S2: 'static + Send, E2: 'static + Send>(
+        mut self,
+        func: Asyn![S, E => S2, Result<R, E2>],
+    ) -> Self::Promise<S2, Result<R, E2>>
+    where
+        S: Into<S2>,
+    {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.err_then(func)),
+        }
+    }
+    #[track_caller]
+    fn catch<S2: 'static + Send>(mut self, func: Asyn![S, E => S2, R]) -> Self::Promise<S2, R>
+    where
+        S: Into<S2>,
+    {
+        let commands = mem::take(&mut self.commands);
+        let promise = mem::take(&mut self.data).unwrap();
+        PromiseChain {
+            commands,
+            promise: Some(promise.catch(func)),
+        }
+    }
+}
+
+impl<'w, 's, 'a, S: 'static + Send, R: 'static + Send, E: 'static + Send> PromiseLikeResult<S, R, E>
+    for PromiseChain<'w, 's, 'a, S, Result<R, E>>
+{
+    #[track_caller]
+    fn ok_then<S2: 'static + Send, R2: 'static + Send>(
+        mut self,
+        func: Asyn![S, R => S2, Result<R2, E>],
+    ) -> Self::Promise<S2, Result<R2, E>>
+    where
+        S: Into<S2>,
+    {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.ok_then(func)),
+        }
+    }
+    #[track_caller]
+    fn err_then<S2: 'static + Send, E2: 'static + Send>(
+        mut self,
+        func: Asyn![S, E => S2, Result<R, E2>],
+    ) -> Self::Promise<S2, Result<R, E2>>
+    where
+        S: Into<S2>,
+    {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.err_then(func)),
+        }
+    }
+    #[track_caller]
+    fn catch<S2: 'static + Send>(mut self, func: Asyn![S, E => S2, R]) -> Self::Promise<S2, R>
+    where
+        S: Into<S2>,
+    {
+        let commands = mem::take(&mut self.commands).unwrap();
+        let promise = mem::take(&mut self.promise).unwrap();
+        PromiseChain {
+            commands: Some(commands),
+            promise: Some(promise.catch(func)),
+        }
+    }
+}
@@ -1,20 +1,43 @@
 //! Core [`Promise`] functionality.
 use bevy::{
-    ecs::system::{BoxedSystem, Command, StaticSystemParam, SystemParam},
+    ecs::system::{BoxedSystem, Command, EntityCommands, StaticSystemParam, SystemParam},
     prelude::*,
+    reflect::{ReflectDeserialize, ReflectSerialize},
     utils::HashMap,
 };
+use diagnostic::PromiseAwaits;
+use panic::PanicStep;
 use pecs_macro::{asyn, impl_all_promises, impl_any_promises};
+use schedule::PromiseSchedule;
+use serde::{Deserialize, Serialize};
 use std::{
     any::type_name,
     cell::RefCell,
     marker::PhantomData,
     mem,
-    sync::{Arc, RwLock},
-    thread::{self, ThreadId},
+    panic::Location,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex, RwLock,
+    },
 };
+use timer::{Elapsed, IntoDuration};
 pub mod app;
+pub mod channel;
+pub mod component;
+pub mod concurrent;
+pub mod condition;
+pub mod coroutine;
+pub mod diagnostic;
+pub mod event;
+pub mod frame;
 mod impls;
+pub mod panic;
+pub mod retry;
+pub mod schedule;
+pub mod state;
+pub mod stream;
+pub mod task;
 pub mod timer;
 pub mod ui;
 
@@ -27,7 +50,7 @@ pub mod ui;
 /// }
 ///
 /// pub struct MyStatefulOps<S>(S);
-/// impl<S: 'static> MyStatefulOps<S> {
+/// impl<S: 'static + Send> MyStatefulOps<S> {
 ///     pub fn func(self) -> Promise<S, ()> {
 ///         my_async_func().with(self.0)
 ///     }
@@ -37,7 +60,7 @@ pub mod ui;
 ///     fn my_async(self) -> MyStatefulOps<S>;
 /// }
 ///
-/// impl<S: 'static> MyAsyncExtension<S> for AsynOps<S> {
+/// impl<S: 'static + Send> MyAsyncExtension<S> for AsynOps<S> {
 ///     fn my_async(self) -> MyStatefulOps<S> {
 ///         MyStatefulOps(self.0)
 ///     }
@@ -65,23 +88,52 @@ impl<T: Clone> Clone for AsynOps<T> {
 }
 impl<T: Copy> Copy for AsynOps<T> {}
 
-pub fn promise_resolve<S: 'static, R: 'static>(world: &mut World, id: PromiseId, state: S, result: R) {
+#[track_caller]
+pub fn promise_resolve<S: 'static + Send, R: 'static + Send>(world: &mut World, id: PromiseId, state: S, result: R) {
+    resolve_promise(world, id, state, result, Location::caller())
+}
+
+fn resolve_promise<S: 'static + Send, R: 'static + Send>(
+    world: &mut World,
+    id: PromiseId,
+    state: S,
+    result: R,
+    location: &'static Location<'static>,
+) {
     // info!(
     //     "resolving {id}<{}, {}>",
     //     type_name::<S>(),
     //     type_name::<R>(),
     // );
-    let registry = world
-        .get_resource_or_insert_with(PromiseRegistry::<S, R>::default)
-        .clone();
-    if let Some(resolve) = {
+    let registry = PromiseRegistry::<S, R>::get(world);
+    let handle = {
+        let read = registry.0.read().unwrap();
+        let Some(prom) = read.get(&id) else {
+            // already discarded (e.g. cancelled via PromiseHandle) or never registered
+            diagnostic::unknown_resolve::<S, R>(world, id, location);
+            return;
+        };
+        prom.handle.clone()
+    };
+    if let Some(handle) = handle {
+        if handle.is_cancelled() {
+            promise_discard::<S, R>(world, id);
+            return;
+        }
+        handle.set_status(HandleStatus::Resolved);
+    }
+    let (resolve, time_scale) = {
         let mut write = registry.0.write().unwrap();
         let prom = write.get_mut(&id).unwrap();
-        mem::take(&mut prom.resolve)
-    } {
-        resolve(world, state, result)
+        (mem::take(&mut prom.resolve), prom.time_scale.unwrap_or(1.))
+    };
+    if let Some(resolve) = resolve {
+        let _chain = diagnostic::enter_chain(world, id);
+        let _scale = timer::enter_scale(time_scale);
+        panic::isolate::<S, R, _>(world, id, PanicStep::Resolve, |world| resolve(world, state, result));
     }
     registry.0.write().unwrap().remove(&id);
+    diagnostic::untrack(world, id, true);
     // info!(
     //     "resolved {id}<{}, {}> ({} left)",
     //     type_name::<S>(),
@@ -90,17 +142,31 @@ pub fn promise_resolve<S: 'static, R: 'static>(world: &mut World, id: PromiseId,
     // );
 }
 
-pub fn promise_register<S: 'static, R: 'static>(world: &mut World, mut promise: Promise<S, R>) {
+pub fn promise_register<S: 'static + Send, R: 'static + Send>(world: &mut World, mut promise: Promise<S, R>) {
     let id = promise.id;
     // info!("registering {id}");
-    let register = promise.register;
-    promise.register = None;
-    let registry = world
-        .get_resource_or_insert_with(PromiseRegistry::<S, R>::default)
-        .clone();
+    let register = mem::take(&mut promise.register);
+    for scope in mem::take(&mut promise.scopes) {
+        let handle = promise.handle();
+        scope(world, handle);
+    }
+    if let Some(handle) = &promise.handle {
+        let cancellations = world.get_resource_or_insert_with(PromiseCancellations::default).clone();
+        if !handle.attach(cancellations) {
+            // cancelled before it was registered
+            diagnostic::drop_quietly(register);
+            return;
+        }
+    }
+    // nested promises inherit the time scale of the chain
+    let time_scale = *promise.time_scale.get_or_insert_with(timer::chain_scale);
+    diagnostic::track(world, &promise);
+    let registry = PromiseRegistry::<S, R>::get(world);
     registry.0.write().unwrap().insert(id, promise);
     if let Some(register) = register {
-        register(world, id)
+        let _chain = diagnostic::enter_chain(world, id);
+        let _scale = timer::enter_scale(time_scale);
+        panic::isolate::<S, R, _>(world, id, PanicStep::Register, |world| register(world, id));
     }
     // info!(
     //     "registered {id}<{}, {}> ({} left)",
@@ -110,14 +176,15 @@ pub fn promise_register<S: 'static, R: 'static>(world: &mut World, mut promise:
     // );
 }
 
-pub fn promise_discard<S: 'static, R: 'static>(world: &mut World, id: PromiseId) {
+pub fn promise_discard<S: 'static + Send, R: 'static + Send>(world: &mut World, id: PromiseId) {
     // info!("discarding {id}");
-    let registry = world
-        .get_resource_or_insert_with(PromiseRegistry::<S, R>::default)
-        .clone();
+    let registry = PromiseRegistry::<S, R>::get(world);
     if let Some(discard) = {
         let mut write = registry.0.write().unwrap();
         if let Some(prom) = write.get_mut(&id) {
+            if let Some(handle) = &prom.handle {
+                handle.set_status(HandleStatus::Discarded);
+            }
             mem::take(&mut prom.discard)
         } else {
             error!(
@@ -131,6 +198,7 @@ pub fn promise_discard<S: 'static, R: 'static>(world: &mut World, id: PromiseId)
         discard(world, id);
     }
     registry.0.write().unwrap().remove(&id);
+    diagnostic::untrack(world, id, false);
     // info!(
     //     "discarded {id}<{}, {}> ({} left)",
     //     type_name::<S>(),
@@ -139,8 +207,8 @@ pub fn promise_discard<S: 'static, R: 'static>(world: &mut World, id: PromiseId)
     // );
 }
 
-pub trait PromiseParams: 'static + SystemParam + Send + Sync {}
-impl<T: 'static + SystemParam + Send + Sync> PromiseParams for T {}
+pub trait PromiseParams: 'static + Send + SystemParam + Send + Sync {}
+impl<T: 'static + Send + SystemParam + Send + Sync> PromiseParams for T {}
 
 /// A wrapper around a system-like function that can be used in various contexts within `pecs`.
 ///
@@ -163,7 +231,7 @@ pub struct Asyn<Input, Output: 'static, Params: PromiseParams> {
 impl<Input, Otput: 'static, Params: PromiseParams> Clone for Asyn<Input, Otput, Params> {
     fn clone(&self) -> Self {
         Asyn {
-            body: self.body.clone(),
+            body: self.body,
             marker: self.marker,
         }
     }
@@ -211,50 +279,60 @@ impl<Input: 'static, Output: 'static, Params: PromiseParams> Asyn<Input, Output,
     /// argument is used to provide access to any necessary `SystemParam`s. The return
     /// value of the `run` method is the output of the system-like function.
     pub fn run(&self, input: Input, world: &mut World) -> Output {
+        let _span = trace_span!("asyn", params = type_name::<Params>()).entered();
         let registry = world
             .get_resource_or_insert_with(SystemRegistry::<Input, Output, Params>::default)
             .clone();
-        let mut write = registry.0.write().unwrap();
         let key = self.clone();
-        let system = write.entry(key).or_insert_with(|| {
-            let mut sys = Box::new(IntoSystem::into_system(self.body));
+        // the system is taken out of the registry while running, so
+        // the registry lock is neither held nor poisoned by the body
+        let cached = registry.0.write().unwrap().remove(&key);
+        let mut system = cached.unwrap_or_else(|| {
+            let mut sys: BoxedSystem<Input, Output> = Box::new(IntoSystem::into_system(self.body));
             sys.initialize(world);
             sys
         });
         let result = system.run(input, world);
         system.apply_deferred(world);
+        registry.0.write().unwrap().insert(key, system);
         result
     }
 }
 
-thread_local!(static PROMISE_LOCAL_ID: std::cell::RefCell<usize>  = RefCell::new(0));
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
-pub struct PromiseId {
-    thread: ThreadId,
-    local: usize,
-}
+static NEXT_PROMISE_ID: AtomicU64 = AtomicU64::new(1);
+
+/// Process-wide unique id of the [`Promise`]. Ids are allocated from the
+/// single atomic counter, so promises created on different threads never
+/// collide, and can be stored or sent over the network as plain `u64`
+/// with [`to_bits()`][PromiseId::to_bits]/[`from_bits()`][PromiseId::from_bits]
+/// or serde.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Reflect, Serialize, Deserialize)]
+#[reflect(Hash, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct PromiseId(u64);
 impl PromiseId {
     pub fn new() -> PromiseId {
-        PROMISE_LOCAL_ID.with(|id| {
-            let mut new_id = id.borrow_mut();
-            *new_id += 1;
-            PromiseId {
-                thread: thread::current().id(),
-                local: *new_id,
-            }
-        })
+        PromiseId(NEXT_PROMISE_ID.fetch_add(1, Ordering::Relaxed))
+    }
+    /// Returns the raw `u64` representation of the id
+    pub fn to_bits(self) -> u64 {
+        self.0
+    }
+    /// Restores the id from the value returned by [`to_bits()`][PromiseId::to_bits]
+    pub fn from_bits(bits: u64) -> PromiseId {
+        PromiseId(bits)
+    }
+}
+
+impl Default for PromiseId {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
 impl std::fmt::Display for PromiseId {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let t = format!("{:?}", self.thread);
-        write!(
-            f,
-            "Promise({}:{})",
-            t.strip_prefix("ThreadId(").unwrap().strip_suffix(")").unwrap(),
-            self.local
-        )
+        write!(f, "Promise({})", self.0)
     }
 }
 
@@ -287,14 +365,23 @@ impl From<()> for PromiseResult<(), ()> {
         PromiseResult::Resolve((), ())
     }
 }
-impl<S: 'static> From<PromiseState<S>> for PromiseResult<S, ()> {
+impl<S: 'static + Send> From<PromiseState<S>> for PromiseResult<S, ()> {
     fn from(state: PromiseState<S>) -> Self {
         PromiseResult::Resolve(state.value, ())
     }
 }
 
-#[derive(Resource)]
+/// Registered promises of the same type. Lives in the world as a non-send resource,
+/// promises only have to be `Send` to be passed through [`Commands`].
 struct PromiseRegistry<S, R>(Arc<RwLock<HashMap<PromiseId, Promise<S, R>>>>);
+impl<S: 'static + Send, R: 'static + Send> PromiseRegistry<S, R> {
+    fn get(world: &mut World) -> Self {
+        if !world.contains_non_send::<PromiseRegistry<S, R>>() {
+            world.insert_non_send_resource(PromiseRegistry::<S, R>::default());
+        }
+        world.non_send_resource::<PromiseRegistry<S, R>>().clone()
+    }
+}
 impl<S, R> Default for PromiseRegistry<S, R> {
     fn default() -> Self {
         PromiseRegistry(Arc::new(RwLock::new(HashMap::new())))
@@ -306,10 +393,10 @@ impl<S, R> Clone for PromiseRegistry<S, R> {
     }
 }
 
+type SystemMap<In, Out, Params> = HashMap<Asyn<In, Out, Params>, BoxedSystem<In, Out>>;
+
 #[derive(Resource)]
-struct SystemRegistry<In, Out: 'static, Params: PromiseParams>(
-    Arc<RwLock<HashMap<Asyn<In, Out, Params>, BoxedSystem<In, Out>>>>,
-);
+struct SystemRegistry<In, Out: 'static, Params: PromiseParams>(Arc<RwLock<SystemMap<In, Out, Params>>>);
 impl<In, Out, Params: PromiseParams> Clone for SystemRegistry<In, Out, Params> {
     fn clone(&self) -> Self {
         SystemRegistry(self.0.clone())
@@ -341,6 +428,30 @@ impl Repeat<()> {
     }
 }
 
+type PromiseCallback = Box<dyn FnOnce(&mut World, PromiseId) + Send>;
+type ResolveCallback<S, R> = Box<dyn FnOnce(&mut World, S, R) + Send>;
+type DiscardFn = fn(&mut World, PromiseId);
+type PromiseScope = Box<dyn FnOnce(&mut World, PromiseHandle) + Send>;
+
+/// Tracks the link a composite promise is currently waiting for, so
+/// discarding the composite promise discards the pending link as well.
+#[derive(Clone)]
+struct PendingLink(Arc<Mutex<Option<(PromiseId, DiscardFn)>>>);
+impl PendingLink {
+    fn new() -> Self {
+        PendingLink(Arc::new(Mutex::new(None)))
+    }
+    fn set<S: 'static + Send, R: 'static + Send>(&self, id: PromiseId) {
+        *self.0.lock().unwrap() = Some((id, promise_discard::<S, R>));
+    }
+    fn discard(&self, world: &mut World) {
+        let pending = self.0.lock().unwrap().take();
+        if let Some((id, discard)) = pending {
+            discard(world, id);
+        }
+    }
+}
+
 /// A promise represents a value that may not be available yet, but will be in the future.
 ///
 /// The promise's state is of type `S`, and the result type is `R`. The state represents the
@@ -362,14 +473,33 @@ impl Repeat<()> {
 /// the next promise state/result arguments are inferred from the result of the previous promise.
 pub struct Promise<S, R> {
     id: PromiseId,
-    register: Option<Box<dyn FnOnce(&mut World, PromiseId)>>,
-    discard: Option<Box<dyn FnOnce(&mut World, PromiseId)>>,
-    resolve: Option<Box<dyn FnOnce(&mut World, S, R)>>,
+    register: Option<PromiseCallback>,
+    discard: Option<PromiseCallback>,
+    resolve: Option<ResolveCallback<S, R>>,
+    handle: Option<PromiseHandle>,
+    scopes: Vec<PromiseScope>,
+    label: Option<String>,
+    time_scale: Option<f64>,
+    awaits: PromiseAwaits,
+    location: &'static Location<'static>,
+}
+
+impl<S, R> Drop for Promise<S, R> {
+    fn drop(&mut self) {
+        if self.register.is_some() {
+            diagnostic::unregistered_drop(self.id, self.label.as_deref(), self.location);
+        }
+        // promises captured by the callbacks are dropped as part of this one
+        diagnostic::drop_quietly((
+            self.register.take(),
+            self.discard.take(),
+            self.resolve.take(),
+            mem::take(&mut self.scopes),
+        ));
+    }
 }
-unsafe impl<S, R> Send for Promise<S, R> {}
-unsafe impl<S, R> Sync for Promise<S, R> {}
 
-impl<S: 'static> Promise<S, ()> {
+impl<S: 'static + Send> Promise<S, ()> {
     /// Creates a new `Promise` with the given initial state `state`.
     ///
     /// The resulting promise resolves immediately with the same state value,
@@ -394,12 +524,13 @@ impl<S: 'static> Promise<S, ()> {
     ///     );
     /// }
     /// ```
+    #[track_caller]
     pub fn from(state: S) -> Promise<S, ()> {
         Self::new(state, asyn!(s => s))
     }
 }
 
-impl<S: 'static, R: 'static> Promise<S, R> {
+impl<S: 'static + Send, R: 'static + Send> Promise<S, R> {
     /// Create new [`Promise`] with empty [state][PromiseState]
     /// ```ignore
     /// # use bevy::prelude::*
@@ -413,6 +544,7 @@ impl<S: 'static, R: 'static> Promise<S, R> {
     ///     );
     /// }
     /// ```
+    #[track_caller]
     pub fn start(func: Asyn![() => S, R]) -> Promise<S, R> {
         Promise::new((), func)
     }
@@ -432,12 +564,22 @@ impl<S: 'static, R: 'static> Promise<S, R> {
     ///     );
     /// }
     /// ```
-    pub fn new<D: 'static>(default_state: D, func: Asyn![D => S, R]) -> Promise<S, R> {
+    #[track_caller]
+    pub fn new<D: 'static + Send>(default_state: D, func: Asyn![D => S, R]) -> Promise<S, R> {
         let id = PromiseId::new();
+        let location = Location::caller();
+        let pending = PendingLink::new();
+        let discard = pending.clone();
         Promise {
             id,
             resolve: None,
-            discard: None,
+            handle: None,
+            scopes: vec![],
+            label: None,
+            time_scale: None,
+            awaits: PromiseAwaits::Chain,
+            location,
+            discard: Some(Box::new(move |world, _id| discard.discard(world))),
             register: Some(Box::new(move |world, id| {
                 // let mut system = world.promise_system(func);
                 // let mut system = IntoSystem::into_system(func.body);
@@ -445,6 +587,7 @@ impl<S: 'static, R: 'static> Promise<S, R> {
                 // let pr = system.run(PromiseState::new(default_state), world).into();
                 // system.apply_buffers(world);
                 // let pr = world.run_promise_system(func, PromiseState::new(default_state)).into();
+                let _step = info_span!("start", promise = %id, at = %location).entered();
                 let pr = func.run((PromiseState::new(default_state), ()), world).into();
                 match pr {
                     PromiseResult::Resolve(s, r) => promise_resolve::<S, R>(world, id, s, r),
@@ -459,6 +602,8 @@ impl<S: 'static, R: 'static> Promise<S, R> {
                             return;
                         }
                         p.resolve = Some(Box::new(move |world, s, r| promise_resolve::<S, R>(world, id, s, r)));
+                        pending.set::<S, R>(p.id);
+                        p.propagate_discard::<S, R>(id);
                         promise_register::<S, R>(world, p);
                     }
                 }
@@ -532,24 +677,103 @@ impl<S: 'static, R: 'static> Promise<S, R> {
     ///     })));
     /// }
     /// ```
-    pub fn register<F: 'static + FnOnce(&mut World, PromiseId), D: 'static + FnOnce(&mut World, PromiseId)>(
+    #[track_caller]
+    pub fn register<
+        F: 'static + Send + FnOnce(&mut World, PromiseId),
+        D: 'static + Send + FnOnce(&mut World, PromiseId),
+    >(
         on_invoke: F,
         on_discard: D,
     ) -> Promise<S, R> {
         Promise {
             id: PromiseId::new(),
             resolve: None,
+            handle: None,
+            scopes: vec![],
+            label: None,
+            time_scale: None,
+            awaits: PromiseAwaits::Custom(type_name::<F>()),
+            location: Location::caller(),
             register: Some(Box::new(on_invoke)),
             discard: Some(Box::new(on_discard)),
         }
     }
 
+    /// Returns a [`PromiseHandle`] that can be used to cancel this promise
+    /// or check its status after it was queued.
+    /// ```ignore
+    /// #[derive(Resource)]
+    /// struct Countdown(PromiseHandle);
+    ///
+    /// fn setup(mut commands: Commands) {
+    ///     let mut promise = asyn::timeout(10.).then(asyn!(_ => {
+    ///         info!("Boom!");
+    ///     }));
+    ///     commands.insert_resource(Countdown(promise.handle()));
+    ///     commands.add(promise);
+    /// }
+    ///
+    /// fn defuse(countdown: Res<Countdown>) {
+    ///     countdown.0.cancel();
+    /// }
+    /// ```
+    pub fn handle(&mut self) -> PromiseHandle {
+        self.handle
+            .get_or_insert_with(|| PromiseHandle::new(self.id, promise_discard::<S, R>))
+            .clone()
+    }
+
+    /// Ties the promise to the `entity`: the promise is discarded when the `entity`
+    /// or any of its ancestors is despawned. The ownership moves to the promises
+    /// chained with [`then()`][PromiseLikeBase::then], so the whole chain is owned:
+    /// ```ignore
+    /// fn show_popup(mut commands: Commands) {
+    ///     let popup = commands.spawn(NodeBundle::default()).id();
+    ///     commands.add(
+    ///         asyn::timeout(5.)
+    ///             .then(asyn!(_ => info!("Popup still here")))
+    ///             .owned_by(popup),
+    ///     );
+    ///     // the timer is discarded and nothing logged
+    ///     commands.entity(popup).despawn_recursive();
+    /// }
+    /// ```
+    pub fn owned_by(self, entity: Entity) -> Promise<S, R> {
+        self.scoped(move |world, handle| {
+            world
+                .get_resource_or_insert_with(PromiseOwners::default)
+                .entry(entity)
+                .or_default()
+                .push(handle);
+        })
+    }
+
+    /// Adds the `scope` callback invoked with the [`PromiseHandle`] of the outermost
+    /// promise of the chain when the chain gets registered. Scopes are used to limit
+    /// the promise lifetime: the handle is cancelled when the scope ends.
+    pub fn scoped<F: 'static + Send + FnOnce(&mut World, PromiseHandle)>(mut self, scope: F) -> Promise<S, R> {
+        self.scopes.push(Box::new(scope));
+        self
+    }
+
+    /// Makes discarding this promise discard the `parent` promise as well.
+    fn propagate_discard<S2: 'static + Send, R2: 'static + Send>(&mut self, parent: PromiseId) {
+        let discard = mem::take(&mut self.discard);
+        self.discard = Some(Box::new(move |world, id| {
+            if let Some(discard) = discard {
+                discard(world, id);
+            }
+            promise_discard::<S2, R2>(world, parent);
+        }));
+    }
+
     /// Create new [`Promise<S, R>`] from default `S` state and  [`Asyn!`][struct@Asyn]`[D => S,`[`Repeat<R>`]`]`
     /// function. `S` and `R` infers from the [`Asyn`][struct@Asyn] function body.
     ///
     /// If `func` resolves with [`Repeat::Continue`] it executes one more time.
     /// If `func` resolves with [`Repeat::Break(result)`], the loop stops and
     /// `result` passes to the next promise.
+    #[track_caller]
     pub fn repeat(state: S, func: Asyn![S => S, Repeat<R>]) -> Promise<S, R> {
         Promise::new(
             (state, func),
@@ -568,7 +792,7 @@ impl<S: 'static, R: 'static> Promise<S, R> {
     }
 }
 
-impl<R: 'static> Promise<(), R> {
+impl<R: 'static + Send> Promise<(), R> {
     /// Create stateless [resolve][PromiseResult::Resolve] with `R` result.
     pub fn resolve(result: R) -> PromiseResult<(), R> {
         PromiseResult::Resolve((), result)
@@ -590,21 +814,27 @@ impl Promise<(), ()> {
 pub struct PromiseCommand<R> {
     id: PromiseId,
     result: R,
+    location: &'static Location<'static>,
 }
 
 impl<R> PromiseCommand<R> {
+    #[track_caller]
     pub fn resolve(id: PromiseId, result: R) -> Self {
-        PromiseCommand { id, result }
+        PromiseCommand {
+            id,
+            result,
+            location: Location::caller(),
+        }
     }
 }
 
 impl<R: 'static + Send + Sync> Command for PromiseCommand<R> {
     fn apply(self, world: &mut World) {
-        promise_resolve::<(), R>(world, self.id, (), self.result);
+        resolve_promise::<(), R>(world, self.id, (), self.result, self.location);
     }
 }
 
-impl<R: 'static, S: 'static> Command for Promise<S, R> {
+impl<R: 'static + Send, S: 'static + Send> Command for Promise<S, R> {
     fn apply(self, world: &mut World) {
         promise_register::<S, R>(world, self)
     }
@@ -612,7 +842,7 @@ impl<R: 'static, S: 'static> Command for Promise<S, R> {
 
 pub trait PromiseCommandsArg {}
 impl PromiseCommandsArg for PromiseId {}
-impl<S: 'static, R: 'static> PromiseCommandsArg for Promise<S, R> {}
+impl<S: 'static + Send, R: 'static + Send> PromiseCommandsArg for Promise<S, R> {}
 
 pub struct PromiseCommands<'w, 's, 'a, T> {
     data: Option<T>,
@@ -620,6 +850,7 @@ pub struct PromiseCommands<'w, 's, 'a, T> {
     finally: Option<fn(&'a mut Commands<'w, 's>, T)>,
 }
 impl<'w, 's, 'a> PromiseCommands<'w, 's, 'a, PromiseId> {
+    #[track_caller]
     pub fn resolve<R: 'static + Send + Sync>(&mut self, value: R) {
         let commands = mem::take(&mut self.commands).unwrap();
         let id = mem::take(&mut self.data).unwrap();
@@ -644,7 +875,7 @@ pub trait PromiseCommandsExtension<'w, 's, T> {
     fn promise<'a>(&'a mut self, promise: T) -> PromiseCommands<'w, 's, 'a, T>;
 }
 
-impl<'w, 's, S: 'static, F: FnOnce() -> S> PromiseCommandsExtension<'w, 's, F> for Commands<'w, 's> {
+impl<'w, 's, S: 'static + Send, F: FnOnce() -> S> PromiseCommandsExtension<'w, 's, F> for Commands<'w, 's> {
     /// Create [`PromiseLike<S, ()>`] chainable commands from default state constructor `|| -> S`
     fn promise<'a>(&'a mut self, arg: F) -> PromiseCommands<'w, 's, 'a, F> {
         PromiseCommands {
@@ -655,6 +886,42 @@ impl<'w, 's, S: 'static, F: FnOnce() -> S> PromiseCommandsExtension<'w, 's, F> f
     }
 }
 
+pub trait PromiseEntityCommandsExtension<'w, 's> {
+    /// Create [`PromiseLike<S, ()>`] chainable commands owned by the entity. The state
+    /// is constructed from the owning entity. The chain is discarded when the entity
+    /// or any of its ancestors is despawned:
+    /// ```ignore
+    /// fn setup(mut commands: Commands) {
+    ///     let popup = commands.spawn(NodeBundle::default()).id();
+    ///     commands
+    ///         .entity(popup)
+    ///         .promise(|popup| popup)
+    ///         .then(asyn!(popup => {
+    ///             info!("Waiting for {:?}", popup.value);
+    ///             popup.asyn().timeout(5.)
+    ///         }))
+    ///         .then(asyn!(popup, mut commands: Commands => {
+    ///             commands.entity(popup.value).despawn_recursive();
+    ///         }));
+    /// }
+    /// ```
+    fn promise<'a, S: 'static + Send, F: FnOnce(Entity) -> S>(
+        &'a mut self,
+        state: F,
+    ) -> PromiseCommands<'w, 's, 'a, Promise<S, ()>>;
+}
+
+impl<'w, 's, 'e> PromiseEntityCommandsExtension<'w, 's> for EntityCommands<'w, 's, 'e> {
+    fn promise<'a, S: 'static + Send, F: FnOnce(Entity) -> S>(
+        &'a mut self,
+        state: F,
+    ) -> PromiseCommands<'w, 's, 'a, Promise<S, ()>> {
+        let entity = self.id();
+        let promise = Promise::from(state(entity)).owned_by(entity);
+        self.commands().promise(promise)
+    }
+}
+
 impl<'w, 's> PromiseCommandsExtension<'w, 's, PromiseId> for Commands<'w, 's> {
     /// Create command for resolving promise by [`PromiseId`]
     fn promise<'a>(&'a mut self, arg: PromiseId) -> PromiseCommands<'w, 's, 'a, PromiseId> {
@@ -666,7 +933,9 @@ impl<'w, 's> PromiseCommandsExtension<'w, 's, PromiseId> for Commands<'w, 's> {
     }
 }
 
-impl<'w, 's, S: 'static, R: 'static> PromiseCommandsExtension<'w, 's, Promise<S, R>> for Commands<'w, 's> {
+impl<'w, 's, S: 'static + Send, R: 'static + Send> PromiseCommandsExtension<'w, 's, Promise<S, R>>
+    for Commands<'w, 's>
+{
     /// Create [`PromiseLike<S, R>`] chainable commands from [`Promise<S, R>`]
     fn promise<'a>(&'a mut self, arg: Promise<S, R>) -> PromiseCommands<'w, 's, 'a, Promise<S, R>> {
         PromiseCommands {
@@ -677,12 +946,36 @@ impl<'w, 's, S: 'static, R: 'static> PromiseCommandsExtension<'w, 's, Promise<S,
     }
 }
 
-pub struct PromiseChain<'w, 's, 'a, S: 'static, R: 'static> {
+pub struct PromiseChain<'w, 's, 'a, S: 'static + Send, R: 'static + Send> {
     commands: Option<&'a mut Commands<'w, 's>>,
     promise: Option<Promise<S, R>>,
 }
 
-impl<'w, 's, 'a, S: 'static, R: 'static> Drop for PromiseChain<'w, 's, 'a, S, R> {
+impl<'w, 's, 'a, S: 'static + Send, R: 'static + Send> PromiseChain<'w, 's, 'a, S, R> {
+    /// Returns a [`PromiseHandle`] of the chain. The chain is still registered
+    /// when dropped, the handle only allows to cancel it later:
+    /// ```ignore
+    /// fn setup(mut commands: Commands) {
+    ///     let handle = commands
+    ///         .promise(|| ())
+    ///         .then(asyn!(s => s.asyn().timeout(5.)))
+    ///         .then(asyn!(_ => info!("Not cancelled")))
+    ///         .handle();
+    ///     commands.insert_resource(MyChain(handle));
+    /// }
+    /// ```
+    pub fn handle(&mut self) -> PromiseHandle {
+        self.promise.as_mut().unwrap().handle()
+    }
+
+    /// Ties the chain to the `entity`, see [`Promise::owned_by()`]
+    pub fn owned_by(mut self, entity: Entity) -> Self {
+        self.promise = self.promise.take().map(|p| p.owned_by(entity));
+        self
+    }
+}
+
+impl<'w, 's, 'a, S: 'static + Send, R: 'static + Send> Drop for PromiseChain<'w, 's, 'a, S, R> {
     fn drop(&mut self) {
         if let Some(commands) = mem::take(&mut self.commands) {
             if let Some(promise) = mem::take(&mut self.promise) {
@@ -692,6 +985,177 @@ impl<'w, 's, 'a, S: 'static, R: 'static> Drop for PromiseChain<'w, 's, 'a, S, R>
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum HandleStatus {
+    Pending,
+    Resolved,
+    Discarded,
+}
+
+struct HandleState {
+    id: PromiseId,
+    discard: DiscardFn,
+    status: HandleStatus,
+    cancelled: bool,
+    cancellations: Option<PromiseCancellations>,
+}
+
+/// Type-erased handle to the queued [`Promise`] or [`PromiseChain`].
+///
+/// Handles are cheap to clone and could be stored in components or
+/// resources. [`cancel()`][PromiseHandle::cancel] discards whichever link of
+/// the chain is currently pending: timers, buttons, http requests, nested
+/// promises and [`any`][Promise::any]/[`all`][Promise::all] children are
+/// cleaned up with their `on_discard` callbacks.
+#[derive(Clone)]
+pub struct PromiseHandle(Arc<Mutex<HandleState>>);
+
+impl PromiseHandle {
+    fn new(id: PromiseId, discard: DiscardFn) -> Self {
+        PromiseHandle(Arc::new(Mutex::new(HandleState {
+            id,
+            discard,
+            status: HandleStatus::Pending,
+            cancelled: false,
+            cancellations: None,
+        })))
+    }
+
+    /// [`PromiseId`] of the promise this handle belongs to.
+    pub fn id(&self) -> PromiseId {
+        self.0.lock().unwrap().id
+    }
+
+    /// Discards the promise right away if it is still pending.
+    fn discard(&self, world: &mut World) {
+        let (id, discard, status) = {
+            let state = self.0.lock().unwrap();
+            (state.id, state.discard, state.status)
+        };
+        if status == HandleStatus::Pending {
+            discard(world, id);
+        }
+    }
+
+    /// Cancels the promise. Promises queued for registration are dropped, registered
+    /// promises are discarded by [`process_cancelled_promises`] system.
+    pub fn cancel(&self) {
+        let mut state = self.0.lock().unwrap();
+        if state.status != HandleStatus::Pending || state.cancelled {
+            return;
+        }
+        state.cancelled = true;
+        if let Some(cancellations) = &state.cancellations {
+            cancellations.0.lock().unwrap().push(self.clone());
+        }
+    }
+
+    /// Returns `true` if the promise neither resolved nor discarded/cancelled yet.
+    pub fn is_pending(&self) -> bool {
+        let state = self.0.lock().unwrap();
+        state.status == HandleStatus::Pending && !state.cancelled
+    }
+
+    /// Returns `true` if the promise is resolved.
+    pub fn is_resolved(&self) -> bool {
+        self.0.lock().unwrap().status == HandleStatus::Resolved
+    }
+
+    fn is_cancelled(&self) -> bool {
+        self.0.lock().unwrap().cancelled
+    }
+
+    fn set_status(&self, status: HandleStatus) {
+        self.0.lock().unwrap().status = status;
+    }
+
+    /// Binds the handle to the world's cancellation queue. Returns `false`
+    /// if the handle was cancelled before the promise got registered.
+    fn attach(&self, cancellations: PromiseCancellations) -> bool {
+        let mut state = self.0.lock().unwrap();
+        if state.cancelled {
+            state.status = HandleStatus::Discarded;
+            false
+        } else {
+            state.cancellations = Some(cancellations);
+            true
+        }
+    }
+}
+
+impl std::fmt::Debug for PromiseHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let state = self.0.lock().unwrap();
+        write!(f, "PromiseHandle({}, {:?})", state.id, state.status)
+    }
+}
+
+/// Handles cancelled via [`PromiseHandle::cancel()`] waiting for discard.
+#[derive(Resource, Clone, Default)]
+pub struct PromiseCancellations(Arc<Mutex<Vec<PromiseHandle>>>);
+
+/// Discards promises cancelled via [`PromiseHandle::cancel()`]
+pub fn process_cancelled_promises(world: &mut World) {
+    let Some(cancellations) = world.get_resource::<PromiseCancellations>().cloned() else {
+        return;
+    };
+    let cancelled = mem::take(&mut *cancellations.0.lock().unwrap());
+    for handle in cancelled {
+        handle.discard(world);
+    }
+}
+
+/// Resolver systems of the generic promises like [`event::asyn::next::<E>()`][event::asyn::next].
+/// The system for the type is added by the first promise created for it and runs every
+/// frame in [`process_promise_resolvers`].
+#[derive(Resource, Default)]
+pub struct PromiseResolvers(Vec<BoxedSystem>);
+impl PromiseResolvers {
+    pub(crate) fn add<M>(world: &mut World, system: impl IntoSystem<(), (), M>) {
+        let mut system: BoxedSystem = Box::new(IntoSystem::into_system(system));
+        system.initialize(world);
+        world.resource_mut::<PromiseResolvers>().0.push(system);
+    }
+}
+
+/// Runs the systems added to [`PromiseResolvers`]
+pub fn process_promise_resolvers(world: &mut World) {
+    // resolved promises may add resolvers for the new types
+    let mut systems = mem::take(&mut world.resource_mut::<PromiseResolvers>().0);
+    for system in systems.iter_mut() {
+        system.run((), world);
+        system.apply_deferred(world);
+    }
+    let added = mem::replace(&mut world.resource_mut::<PromiseResolvers>().0, systems);
+    world.resource_mut::<PromiseResolvers>().0.extend(added);
+}
+
+/// Promises owned by entities via [`Promise::owned_by()`].
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct PromiseOwners(HashMap<Entity, Vec<PromiseHandle>>);
+
+/// Cancels promises which owner or its ancestors got despawned.
+pub fn discard_orphaned_promises(mut owners: ResMut<PromiseOwners>, entities: Query<Option<&Parent>>) {
+    owners.retain(|owner, handles| {
+        handles.retain(|handle| handle.is_pending());
+        if handles.is_empty() {
+            return false;
+        }
+        let mut entity = *owner;
+        let alive = loop {
+            match entities.get(entity) {
+                Err(_) => break false,
+                Ok(None) => break true,
+                Ok(Some(parent)) => entity = parent.get(),
+            }
+        };
+        if !alive {
+            handles.iter().for_each(PromiseHandle::cancel);
+        }
+        alive
+    });
+}
+
 /// A wrapper for state that can be passed to asynchronous functions and promises.
 ///
 /// The state wrapped by `PromiseState` is passed to the next function or promise in the chain.
@@ -734,7 +1198,7 @@ impl<'w, 's, 'a, S: 'static, R: 'static> Drop for PromiseChain<'w, 's, 'a, S, R>
 pub struct PromiseState<S> {
     pub value: S,
 }
-impl<S: 'static> PromiseState<S> {
+impl<S: 'static + Send> PromiseState<S> {
     /// Create a new `PromiseState` with the given initial value.
     pub fn new(value: S) -> PromiseState<S> {
         PromiseState { value }
@@ -772,22 +1236,24 @@ impl<S: 'static> PromiseState<S> {
     }
 
     /// Create a new `PromiseState` by mapping the current value with the given function.
-    pub fn map<S2: 'static, F: FnOnce(S) -> S2>(self, map: F) -> PromiseState<S2> {
+    pub fn map<S2: 'static + Send, F: FnOnce(S) -> S2>(self, map: F) -> PromiseState<S2> {
         PromiseState { value: map(self.value) }
     }
 
     /// Create a new `PromiseState` with the given value.
-    pub fn with<S2: 'static>(self, value: S2) -> PromiseState<S2> {
+    pub fn with<S2: 'static + Send>(self, value: S2) -> PromiseState<S2> {
         PromiseState { value }
     }
 
     /// Start a new promise chain with the given asynchronous function.
-    pub fn start<S2: 'static, R2: 'static>(self, func: Asyn![S => S2, R2]) -> Promise<S2, R2> {
+    #[track_caller]
+    pub fn start<S2: 'static + Send, R2: 'static + Send>(self, func: Asyn![S => S2, R2]) -> Promise<S2, R2> {
         Promise::new(self.value, func)
     }
 
     /// Start a new promise loop with the given asynchronous function.
-    pub fn repeat<R2: 'static>(self, func: Asyn![S => S, Repeat<R2>]) -> Promise<S, R2> {
+    #[track_caller]
+    pub fn repeat<R2: 'static + Send>(self, func: Asyn![S => S, Repeat<R2>]) -> Promise<S, R2> {
         Promise::repeat(self.value, func)
     }
 
@@ -800,6 +1266,34 @@ impl<S: 'static> PromiseState<S> {
     pub fn all<A: AllPromises>(self, all: A) -> Promise<S, A::Result> {
         all.register().with(self.value)
     }
+
+    /// Stateful version of [`timeout()`][PromiseLikeTimeout::timeout]: the state is moved out
+    /// before the race, so it passes to the next promise even if the `promise` is discarded
+    /// on timeout.
+    /// ```ignore
+    /// asyn!(s => {
+    ///     s.timeout(asyn::http::get("https://bevyengine.org").send(), 2.0)
+    /// })
+    /// ```
+    #[track_caller]
+    pub fn timeout<R: 'static + Send>(
+        self,
+        promise: Promise<(), R>,
+        duration: impl IntoDuration,
+    ) -> Promise<S, Result<R, Elapsed>> {
+        promise.timeout(duration).with(self.value)
+    }
+
+    /// Stateful version of [`timeout_or()`][PromiseLikeTimeout::timeout_or]
+    #[track_caller]
+    pub fn timeout_or<R: 'static + Send>(
+        self,
+        promise: Promise<(), R>,
+        duration: impl IntoDuration,
+        default: R,
+    ) -> Promise<S, R> {
+        promise.timeout_or(duration, default).with(self.value)
+    }
 }
 
 impl<S: std::fmt::Display> std::fmt::Display for PromiseState<S> {
@@ -808,13 +1302,13 @@ impl<S: std::fmt::Display> std::fmt::Display for PromiseState<S> {
     }
 }
 
-impl<S: 'static> std::ops::Deref for PromiseState<S> {
+impl<S: 'static + Send> std::ops::Deref for PromiseState<S> {
     type Target = S;
     fn deref(&self) -> &Self::Target {
         &self.value
     }
 }
-impl<S: 'static> std::ops::DerefMut for PromiseState<S> {
+impl<S: 'static + Send> std::ops::DerefMut for PromiseState<S> {
     fn deref_mut(&mut self) -> &mut Self::Target {
         &mut self.value
     }
@@ -826,85 +1320,72 @@ impl<T: std::fmt::Debug> std::fmt::Debug for PromiseState<T> {
     }
 }
 
-pub struct MutPtr<T>(*mut T);
-unsafe impl<T> Send for MutPtr<T> {}
-unsafe impl<T> Sync for MutPtr<T> {}
-impl<T> Clone for MutPtr<T> {
-    fn clone(&self) -> Self {
-        MutPtr(self.0)
-    }
-}
-
-impl<T> MutPtr<T> {
-    pub fn new(value: T) -> MutPtr<T> {
-        let b = Box::new(value);
-        MutPtr(Box::leak(b) as *mut T)
-    }
-    pub fn get(&mut self) -> T {
-        if self.0.is_null() {
-            panic!("Ups.")
+/// Partial results of the [`AnyPromises`]/[`AllPromises`] keyed by the id of the
+/// combined promise. Lives in the world as a non-send resource, so the results are
+/// not required to be `Send`. The entry is removed when the combined promise resolves
+/// or gets discarded.
+struct Combined<T>(HashMap<PromiseId, T>);
+impl<T: 'static> Combined<T> {
+    fn insert(world: &mut World, id: PromiseId, value: T) {
+        if !world.contains_non_send::<Combined<T>>() {
+            world.insert_non_send_resource(Combined::<T>(HashMap::new()));
         }
-        let b = unsafe { Box::from_raw(self.0) };
-        self.0 = std::ptr::null_mut();
-        *b
+        world.non_send_resource_mut::<Combined<T>>().0.insert(id, value);
     }
-    pub fn get_ref(&self) -> &T {
-        if self.0.is_null() {
-            panic!("Ups.");
-        }
-        unsafe { self.0.as_ref().unwrap() }
+    fn get_mut(world: &mut World, id: PromiseId) -> Option<&mut T> {
+        world
+            .get_non_send_resource_mut::<Combined<T>>()?
+            .into_inner()
+            .0
+            .get_mut(&id)
     }
-    pub fn get_mut(&mut self) -> &mut T {
-        if self.0.is_null() {
-            panic!("Ups.");
-        }
-        unsafe { self.0.as_mut().unwrap() }
-    }
-    pub fn is_valid(&self) -> bool {
-        !self.0.is_null()
+    fn remove(world: &mut World, id: PromiseId) -> Option<T> {
+        world.get_non_send_resource_mut::<Combined<T>>()?.0.remove(&id)
     }
 }
 
 pub trait AnyPromises {
-    type Result: 'static;
+    type Result: 'static + Send;
     fn register(self) -> Promise<(), Self::Result>;
 }
 pub trait AllPromises {
-    type Result: 'static;
+    type Result: 'static + Send;
     fn register(self) -> Promise<(), Self::Result>;
 }
 
-impl<S: 'static, R: 'static> AnyPromises for Vec<Promise<S, R>> {
+impl<S: 'static + Send, R: 'static + Send> AnyPromises for Vec<Promise<S, R>> {
     type Result = (S, R);
     fn register(self) -> Promise<(), Self::Result> {
         let ids: Vec<PromiseId> = self.iter().map(|p| p.id).collect();
         let discard_ids = ids.clone();
         Promise::register(
             move |world, any_id| {
-                let mut idx = 0usize;
-                for promise in self {
-                    let ids = ids.clone();
+                Combined::insert(world, any_id, ids);
+                for (idx, promise) in self.into_iter().enumerate() {
                     promise_register(
                         world,
-                        promise.map(move |s| (s, any_id, idx, ids)).then(asyn!(|s, r| {
-                            let (state, any_id, idx, ids) = s.value;
+                        promise.map(move |s| (s, any_id, idx)).then(asyn!(|s, r| {
+                            let (state, any_id, idx) = s.value;
                             Promise::<(), ()>::register(
-                                move |world, _id| {
-                                    for (i, id) in ids.iter().enumerate() {
-                                        if i != idx {
-                                            promise_discard::<S, R>(world, *id);
+                                move |world, id| {
+                                    if let Some(ids) = Combined::<Vec<PromiseId>>::remove(world, any_id) {
+                                        for (i, id) in ids.into_iter().enumerate() {
+                                            if i != idx {
+                                                promise_discard::<S, R>(world, id);
+                                            }
                                         }
+                                        promise_resolve::<(), (S, R)>(world, any_id, (), (state, r));
                                     }
-                                    promise_resolve::<(), (S, R)>(world, any_id, (), (state, r))
+                                    promise_resolve::<(), ()>(world, id, (), ());
                                 },
                                 |_, _| {},
                             )
                         })),
                     );
-                    idx += 1;
                 }
             },
-            move |world, _| {
+            move |world, any_id| {
+                Combined::<Vec<PromiseId>>::remove(world, any_id);
                 for id in discard_ids {
                     promise_discard::<S, R>(world, id);
                 }
@@ -913,38 +1394,43 @@ impl<S: 'static, R: 'static> AnyPromises for Vec<Promise<S, R>> {
     }
 }
 
-impl<S: 'static, R: 'static> AllPromises for Vec<Promise<S, R>> {
+impl<S: 'static + Send, R: 'static + Send> AllPromises for Vec<Promise<S, R>> {
     type Result = Vec<(S, R)>;
     fn register(self) -> Promise<(), Self::Result> {
         let ids: Vec<PromiseId> = self.iter().map(|p| p.id).collect();
         let size = ids.len();
         Promise::register(
-            move |world, any_id| {
+            move |world, all_id| {
                 let value: Vec<Option<(S, R)>> = (0..size).map(|_| None).collect();
-                let value = MutPtr::new(value);
-                let mut idx = 0usize;
-                for promise in self {
-                    let value = value.clone();
+                Combined::insert(world, all_id, value);
+                for (idx, promise) in self.into_iter().enumerate() {
                     promise_register(
                         world,
-                        promise.map(move |s| (s, any_id, idx, value)).then(asyn!(|s, r| {
-                            let (s, any_id, idx, mut value) = s.value;
+                        promise.map(move |s| (s, all_id, idx)).then(asyn!(|s, r| {
+                            let (s, all_id, idx) = s.value;
                             Promise::<(), ()>::register(
-                                move |world, _id| {
-                                    value.get_mut()[idx] = Some((s, r));
-                                    if value.get_ref().iter().all(|v| v.is_some()) {
-                                        let value = value.get().into_iter().map(|v| v.unwrap()).collect();
-                                        promise_resolve::<(), Vec<(S, R)>>(world, any_id, (), value)
+                                move |world, id| {
+                                    let done = Combined::<Vec<Option<(S, R)>>>::get_mut(world, all_id)
+                                        .map(|value| {
+                                            value[idx] = Some((s, r));
+                                            value.iter().all(|v| v.is_some())
+                                        })
+                                        .unwrap_or(false);
+                                    if done {
+                                        let value = Combined::<Vec<Option<(S, R)>>>::remove(world, all_id).unwrap();
+                                        let value = value.into_iter().map(|v| v.unwrap()).collect();
+                                        promise_resolve::<(), Vec<(S, R)>>(world, all_id, (), value)
                                     }
+                                    promise_resolve::<(), ()>(world, id, (), ());
                                 },
                                 |_, _| {},
                             )
                         })),
                     );
-                    idx += 1;
                 }
             },
-            move |world, _| {
+            move |world, all_id| {
+                Combined::<Vec<Option<(S, R)>>>::remove(world, all_id);
                 for id in ids {
                     promise_discard::<S, R>(world, id);
                 }
@@ -968,8 +1454,8 @@ macro_rules! Asyn {
     }
 }
 
-pub struct Promises<S: 'static, R: 'static>(Vec<Promise<S, R>>);
-impl<S: 'static, R: 'static> Promises<S, R> {
+pub struct Promises<S: 'static + Send, R: 'static + Send>(Vec<Promise<S, R>>);
+impl<S: 'static + Send, R: 'static + Send> Promises<S, R> {
     pub fn any(self) -> Promise<(), (S, R)> {
         PromiseState::new(()).any(self.0)
     }
@@ -978,39 +1464,39 @@ impl<S: 'static, R: 'static> Promises<S, R> {
     }
 }
 
-pub trait PromisesExtension<S: 'static, R: 'static> {
+pub trait PromisesExtension<S: 'static + Send, R: 'static + Send> {
     fn promise(self) -> Promises<S, R>;
 }
 
-impl<S: 'static, R: 'static, I: Iterator<Item = Promise<S, R>>> PromisesExtension<S, R> for I {
+impl<S: 'static + Send, R: 'static + Send, I: Iterator<Item = Promise<S, R>>> PromisesExtension<S, R> for I {
     fn promise(self) -> Promises<S, R> {
         Promises(self.collect())
     }
 }
 
-pub trait PromiseLikeBase<S: 'static, R: 'static>
+pub trait PromiseLikeBase<S: 'static + Send, R: 'static + Send>
 where
     Self: Sized,
 {
-    type Promise<S2: 'static, R2: 'static>;
+    type Promise<S2: 'static + Send, R2: 'static + Send>;
     /// Schedule the next [`Asyn![S, R => S2, R2]`][Asyn!] func invocation after current promise resolve.
     /// `S2` and `R2` infers from the `func` body
-    fn then<S2: 'static, R2: 'static>(self, func: Asyn![S, R => S2, R2]) -> Self::Promise<S2, R2>;
+    fn then<S2: 'static + Send, R2: 'static + Send>(self, func: Asyn![S, R => S2, R2]) -> Self::Promise<S2, R2>;
 
     /// Create new [`PromiseLike<S, R>`] from previouse promise with result mapped by `map` from `R` to `R2`
-    fn map_result<R2: 'static, F: 'static + FnOnce(R) -> R2>(self, map: F) -> Self::Promise<S, R2>;
+    fn map_result<R2: 'static + Send, F: 'static + Send + FnOnce(R) -> R2>(self, map: F) -> Self::Promise<S, R2>;
 
     /// Create new [`PromiseLike<S, R2>`] from previouse promise with new result `R2`
-    fn with_result<R2: 'static>(self, value: R2) -> Self::Promise<S, R2>;
+    fn with_result<R2: 'static + Send>(self, value: R2) -> Self::Promise<S, R2>;
 
     /// Create new [`PromiseLike<S2, R>`] from previouse promise with state mapped by `map` from `S` to `S2`
-    fn map<S2: 'static, F: 'static + FnOnce(S) -> S2>(self, map: F) -> Self::Promise<S2, R>;
+    fn map<S2: 'static + Send, F: 'static + Send + FnOnce(S) -> S2>(self, map: F) -> Self::Promise<S2, R>;
 
     /// Create new [`PromiseLike<S2, R>`] from previouse promise with state replaced with `S2`
-    fn with<S2: 'static>(self, state: S2) -> Self::Promise<S2, R>;
+    fn with<S2: 'static + Send>(self, state: S2) -> Self::Promise<S2, R>;
 }
 
-pub trait PromiseLike<S: 'static>
+pub trait PromiseLike<S: 'static + Send>
 where
     Self: Sized + PromiseLikeBase<S, ()>,
 {
@@ -1019,11 +1505,107 @@ where
     /// If `func` resolves with `Repeat::Continue` it executes one more time.
     /// If `func` resolves with `Repeat::Break(result)`, the loop stops and
     /// `result` passes to the next promise.
-    fn then_repeat<R2: 'static>(self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2>;
+    fn then_repeat<R2: 'static + Send>(self, func: Asyn![S => S, Repeat<R2>]) -> Self::Promise<S, R2>;
 
     /// Create a new promise that resolves when all promises in the `all` parameter have resolved.
-    fn all<A: 'static + AllPromises>(self, all: A) -> Self::Promise<S, A::Result>;
+    fn all<A: 'static + Send + AllPromises>(self, all: A) -> Self::Promise<S, A::Result>;
 
     /// Create a new promise that resolves when any of the promises in the `any` parameter have resolved.
-    fn any<A: 'static + AnyPromises>(self, any: A) -> Self::Promise<S, A::Result>;
+    fn any<A: 'static + Send + AnyPromises>(self, any: A) -> Self::Promise<S, A::Result>;
+}
+
+/// Deadlines for stateless promises. The state can't be recovered from the promise
+/// discarded on timeout, so stateful chains race the stateless promise with
+/// [`PromiseState::timeout()`], which keeps the state outside of the race:
+/// ```ignore
+/// asyn!(s => {
+///     s.timeout(asyn::http::get("https://bevyengine.org").send(), 2.0)
+/// })
+/// ```
+pub trait PromiseLikeTimeout<R: 'static + Send>
+where
+    Self: Sized + PromiseLikeBase<(), R>,
+{
+    /// Create new [`PromiseLike<(), Result<R, Elapsed>>`] that resolves with `Err(Elapsed)` if the
+    /// previous promise is not resolved within `duration`. The previous promise is discarded
+    /// on timeout, so its timers and tasks are cleaned up.
+    fn timeout(self, duration: impl IntoDuration) -> Self::Promise<(), Result<R, Elapsed>>;
+
+    /// Same as [`timeout()`][PromiseLikeTimeout::timeout], but resolves with `default` on timeout
+    fn timeout_or(self, duration: impl IntoDuration, default: R) -> Self::Promise<(), R>;
+}
+
+/// Error channel for promises resolving with [`Result<R, E>`]. An `Err` skips every
+/// [`ok_then()`][PromiseLikeResult::ok_then] step of the chain and lands in the first
+/// [`err_then()`][PromiseLikeResult::err_then]/[`catch()`][PromiseLikeResult::catch]
+/// handler:
+/// ```ignore
+/// fn setup(mut commands: Commands) {
+///     commands
+///         .promise(|| ())
+///         .then(asyn!(s => s.asyn().http().get("https://bevyengine.org")))
+///         // only called if the first request succeeds
+///         .ok_then(asyn!(s, response => {
+///             info!("Bevy responded with {}", response.status);
+///             s.asyn().http().get("https://github.com")
+///         }))
+///         .ok_then(asyn!(s, response => {
+///             s.resolve(Ok(response.bytes.len()))
+///         }))
+///         // called if any request fails
+///         .catch(asyn!(s, err => {
+///             error!("Request failed: {err}");
+///             s.resolve(0)
+///         }))
+///         .then(asyn!(s, size => {
+///             info!("Got {size} bytes");
+///         }));
+/// }
+/// ```
+pub trait PromiseLikeResult<S: 'static + Send, R: 'static + Send, E: 'static + Send>
+where
+    Self: Sized + PromiseLikeBase<S, Result<R, E>>,
+{
+    /// Schedule the next [`Asyn![S, R => S2, Result<R2, E>]`][Asyn!] func invocation if the current promise
+    /// resolves with `Ok(R)`. `Err(E)` passes to the next promise without calling `func`, the state
+    /// is converted to `S2` with [`Into`].
+    fn ok_then<S2: 'static + Send, R2: 'static + Send>(
+        self,
+        func: Asyn![S, R => S2, Result<R2, E>],
+    ) -> Self::Promise<S2, Result<R2, E>>
+    where
+        S: Into<S2>;
+
+    /// Schedule the next [`Asyn![S, E => S2, Result<R, E2>]`][Asyn!] func invocation if the current promise
+    /// resolves with `Err(E)`. `Ok(R)` passes to the next promise without calling `func`, the state
+    /// is converted to `S2` with [`Into`].
+    fn err_then<S2: 'static + Send, E2: 'static + Send>(
+        self,
+        func: Asyn![S, E => S2, Result<R, E2>],
+    ) -> Self::Promise<S2, Result<R, E2>>
+    where
+        S: Into<S2>;
+
+    /// Handle `Err(E)` with [`Asyn![S, E => S2, R]`][Asyn!] func, producing the promise with plain `R` result.
+    /// `Ok(R)` passes to the next promise unwrapped without calling `func`, the state is converted
+    /// to `S2` with [`Into`].
+    fn catch<S2: 'static + Send>(self, func: Asyn![S, E => S2, R]) -> Self::Promise<S2, R>
+    where
+        S: Into<S2>;
+
+    /// Create new [`PromiseLike<S, Result<R, E2>>`] from previous promise with error mapped by `map` from `E` to `E2`
+    fn map_err<E2: 'static + Send, F: 'static + Send + FnOnce(E) -> E2>(
+        self,
+        map: F,
+    ) -> Self::Promise<S, Result<R, E2>> {
+        self.map_result(|result| result.map_err(map))
+    }
+
+    /// Create new [`PromiseLike<S, Result<R, E2>>`] from previous promise with error recovered by `op`
+    fn or_else<E2: 'static + Send, F: 'static + Send + FnOnce(E) -> Result<R, E2>>(
+        self,
+        op: F,
+    ) -> Self::Promise<S, Result<R, E2>> {
+        self.map_result(|result| result.or_else(op))
+    }
 }
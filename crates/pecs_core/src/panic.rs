@@ -0,0 +1,155 @@
+//! Panic isolation for promise callbacks
+use std::{
+    any::Any,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+};
+
+use super::*;
+
+/// Controls what happens when an [`Asyn`][struct@Asyn] function or a
+/// [`Promise::register()`] callback panics. Set it on the plugin to opt in:
+/// ```ignore
+/// App::new()
+///     .add_plugins(DefaultPlugins)
+///     .add_plugins(PecsPlugin::panic_policy(PromisePanicPolicy::LogAndDiscard))
+///     .run();
+/// ```
+/// The plugin stores the policy in this resource, replacing the resource changes
+/// the policy at runtime.
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum PromisePanicPolicy {
+    /// Panics are not caught and tear down the app.
+    #[default]
+    Abort,
+    /// Panics are logged with the promise id and step, the affected chain is discarded.
+    LogAndDiscard,
+    /// Panics inside [`ok_then()`][PromiseLikeResult::ok_then]/[`err_then()`][PromiseLikeResult::err_then]
+    /// steps resolve the step with `Err`. The state is moved into the panicked function, so only
+    /// stateless steps (producing `()` state) with [`PromisePanic`] or [`String`] error type can be
+    /// converted. Panics in other steps are logged with the reason and handled as with
+    /// [`LogAndDiscard`][PromisePanicPolicy::LogAndDiscard].
+    ConvertToError,
+}
+
+/// Promise step that panicked
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum PanicStep {
+    /// [`Promise::register()`] `on_invoke` callback or initial [`Promise::new()`] function
+    Register,
+    /// Chained function called with the result of the promise
+    Resolve,
+}
+
+impl std::fmt::Display for PanicStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PanicStep::Register => write!(f, "register"),
+            PanicStep::Resolve => write!(f, "resolve"),
+        }
+    }
+}
+
+/// Error produced from the caught panic
+#[derive(Clone, Debug)]
+pub struct PromisePanic {
+    /// Id of the promise which callback panicked
+    pub promise: PromiseId,
+    pub step: PanicStep,
+    /// Panic payload message
+    pub message: String,
+}
+
+impl std::fmt::Display for PromisePanic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} panicked on {}: {}", self.promise, self.step, self.message)
+    }
+}
+
+impl std::error::Error for PromisePanic {}
+
+fn policy(world: &World) -> PromisePanicPolicy {
+    world.get_resource::<PromisePanicPolicy>().copied().unwrap_or_default()
+}
+
+fn message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Box<dyn Any>".to_string()
+    }
+}
+
+fn downcast<T: 'static + Send>(value: Box<dyn Any>) -> Option<T> {
+    value.downcast::<T>().ok().map(|value| *value)
+}
+
+/// Runs promise `step` of the `id` promise. If the step panics and the policy allows,
+/// the panic is logged, the promise is discarded and `false` is returned.
+pub(crate) fn isolate<S: 'static + Send, R: 'static + Send, F: FnOnce(&mut World)>(
+    world: &mut World,
+    id: PromiseId,
+    step: PanicStep,
+    f: F,
+) -> bool {
+    if policy(world) == PromisePanicPolicy::Abort {
+        f(world);
+        return true;
+    }
+    let Err(payload) = catch_unwind(AssertUnwindSafe(|| f(world))) else {
+        return true;
+    };
+    error!(
+        "{id}<{}, {}> panicked on {step}: {}, discarding the chain",
+        type_name::<S>(),
+        type_name::<R>(),
+        message(payload.as_ref())
+    );
+    promise_discard::<S, R>(world, id);
+    false
+}
+
+/// Runs fallible step of the `id` promise converting panics into `Err` when
+/// [`PromisePanicPolicy::ConvertToError`] is used and types allow.
+pub(crate) fn convert<
+    S: 'static + Send,
+    R: 'static + Send,
+    E: 'static + Send,
+    F: FnOnce(&mut World) -> PromiseResult<S, Result<R, E>>,
+>(
+    world: &mut World,
+    id: PromiseId,
+    f: F,
+) -> PromiseResult<S, Result<R, E>> {
+    if policy(world) != PromisePanicPolicy::ConvertToError {
+        return f(world);
+    }
+    let payload = match catch_unwind(AssertUnwindSafe(|| f(world))) {
+        Ok(result) => return result,
+        Err(payload) => payload,
+    };
+    let panic = PromisePanic {
+        promise: id,
+        step: PanicStep::Resolve,
+        message: message(payload.as_ref()),
+    };
+    // the state is lost with the panicked function, so it can only be restored when it is `()`
+    let Some(state) = downcast::<S>(Box::new(())) else {
+        warn!(
+            "{panic}, can't convert to error: the state {} is not ()",
+            type_name::<S>()
+        );
+        resume_unwind(payload)
+    };
+    let err = downcast::<E>(Box::new(panic.clone())).or_else(|| downcast::<E>(Box::new(panic.to_string())));
+    let Some(err) = err else {
+        warn!(
+            "{panic}, can't convert to error: the error {} is not PromisePanic or String",
+            type_name::<E>()
+        );
+        resume_unwind(payload)
+    };
+    warn!("{panic}, resolving with error");
+    PromiseResult::Resolve(state, Err(err))
+}
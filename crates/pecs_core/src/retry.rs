@@ -0,0 +1,200 @@
+//! Retry fallible promises with backoff
+//! ```ignore
+//! fn setup(mut commands: Commands) {
+//!     let policy = RetryPolicy::new(5)
+//!         .exponential(0.5, 2.0)
+//!         .jitter(0.2)
+//!         .retry_if(|err: &String| !err.contains("404"));
+//!     commands.add(
+//!         Promise::retry(policy, asyn!(_ => asyn::http::get("https://bevyengine.org").send()))
+//!             .then(asyn!(_, retried => {
+//!                 info!("Finished after {} attempts: {:?}", retried.attempts, retried.result.map(|r| r.status));
+//!             })),
+//!     );
+//! }
+//! ```
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+};
+
+use super::*;
+
+/// Delay between the failed attempt and the next one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Backoff {
+    /// Waits the same amount of seconds after every failed attempt.
+    Fixed(f32),
+    /// Waits `initial * factor.powi(attempt - 1)` seconds after the failed
+    /// `attempt`, but no longer than `max` seconds.
+    Exponential { initial: f32, factor: f32, max: f32 },
+}
+
+type RetryPredicate<E> = Arc<dyn Fn(&E) -> bool + Send + Sync>;
+
+/// Controls how [`Promise::retry()`] repeats failed attempts. Retries every error
+/// without delay unless configured otherwise.
+pub struct RetryPolicy<E> {
+    max_attempts: u32,
+    backoff: Backoff,
+    jitter: f32,
+    retry_if: Option<RetryPredicate<E>>,
+}
+
+impl<E> Clone for RetryPolicy<E> {
+    fn clone(&self) -> Self {
+        RetryPolicy {
+            max_attempts: self.max_attempts,
+            backoff: self.backoff,
+            jitter: self.jitter,
+            retry_if: self.retry_if.clone(),
+        }
+    }
+}
+
+impl<E> RetryPolicy<E> {
+    /// Creates policy that makes up to `max_attempts` attempts, including the first one.
+    pub fn new(max_attempts: u32) -> Self {
+        RetryPolicy {
+            max_attempts: max_attempts.max(1),
+            backoff: Backoff::Fixed(0.),
+            jitter: 0.,
+            retry_if: None,
+        }
+    }
+    /// Waits `secs` seconds between attempts
+    pub fn fixed(mut self, secs: f32) -> Self {
+        self.backoff = Backoff::Fixed(secs);
+        self
+    }
+    /// Waits `initial` seconds after the first failure, multiplying the delay by `factor` after each next one
+    pub fn exponential(mut self, initial: f32, factor: f32) -> Self {
+        self.backoff = Backoff::Exponential {
+            initial,
+            factor,
+            max: f32::INFINITY,
+        };
+        self
+    }
+    /// Limits the exponential backoff delay with `secs` seconds
+    pub fn max_delay(mut self, secs: f32) -> Self {
+        if let Backoff::Exponential { max, .. } = &mut self.backoff {
+            *max = secs;
+        }
+        self
+    }
+    /// Randomly scales every delay by up to `fraction` in both directions,
+    /// so `0.25` turns the 1 second delay into 0.75..1.25 seconds.
+    pub fn jitter(mut self, fraction: f32) -> Self {
+        self.jitter = fraction.clamp(0., 1.);
+        self
+    }
+    /// Retries only errors `predicate` returns `true` for, other errors resolve the promise right away
+    pub fn retry_if<F: 'static + Send + Fn(&E) -> bool + Send + Sync>(mut self, predicate: F) -> Self {
+        self.retry_if = Some(Arc::new(predicate));
+        self
+    }
+    /// Maximum number of attempts, including the first one
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+    /// Returns the delay in seconds before the next attempt after the failed `attempt` (starting from 1)
+    pub fn delay(&self, attempt: u32) -> f32 {
+        let delay = match self.backoff {
+            Backoff::Fixed(secs) => secs,
+            Backoff::Exponential { initial, factor, max } => {
+                (initial * factor.powi(attempt.saturating_sub(1) as i32)).min(max)
+            }
+        };
+        if self.jitter > 0. {
+            (delay * (1. + self.jitter * (2. * random() - 1.))).max(0.)
+        } else {
+            delay
+        }
+    }
+    fn should_retry(&self, attempt: u32, error: &E) -> bool {
+        attempt < self.max_attempts
+            && match &self.retry_if {
+                Some(retry_if) => retry_if(error),
+                None => true,
+            }
+    }
+}
+
+/// Result of the [`Promise::retry()`] loop.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Retried<R, E> {
+    /// Result of the last attempt
+    pub result: Result<R, E>,
+    /// Number of attempts made, including the last one
+    pub attempts: u32,
+}
+
+impl<R, E> Retried<R, E> {
+    pub fn into_result(self) -> Result<R, E> {
+        self.result
+    }
+}
+
+impl<R: 'static + Send, E: 'static + Send> Promise<(), Retried<R, E>> {
+    /// Create new [`Promise`] that calls `func` and awaits the promise it returns until
+    /// it resolves with `Ok`, the error is not retryable or `policy` runs out of attempts.
+    /// The failed attempts are followed by the backoff delay driven by [`timeout()`][timer::timeout].
+    #[track_caller]
+    pub fn retry(policy: RetryPolicy<E>, func: Asyn![() => (), Result<R, E>]) -> Promise<(), Retried<R, E>> {
+        PromiseState::new(()).retry(policy, func)
+    }
+}
+
+impl<S: 'static + Send> PromiseState<S> {
+    /// Start a new [`retry()`][Promise::retry] loop, the state is passed from one attempt to the next one.
+    #[track_caller]
+    pub fn retry<R: 'static + Send, E: 'static + Send>(
+        self,
+        policy: RetryPolicy<E>,
+        func: Asyn![S => S, Result<R, E>],
+    ) -> Promise<S, Retried<R, E>> {
+        attempt(self.value, 1, policy, func)
+    }
+}
+
+#[track_caller]
+fn attempt<
+    S: 'static + Send,
+    R: 'static + Send,
+    E: 'static + Send,
+    O: 'static + Into<PromiseResult<S, Result<R, E>>>,
+    P: PromiseParams,
+>(
+    state: S,
+    attempt: u32,
+    policy: RetryPolicy<E>,
+    func: Asyn<(PromiseState<S>, ()), O, P>,
+) -> Promise<S, Retried<R, E>> {
+    let next = func.clone();
+    Promise::new(state, func)
+        .map(move |state| (state, attempt, policy, next))
+        .then(asyn!(s, result => {
+            let (state, attempt, policy, next) = s.value;
+            match result {
+                Err(error) if policy.should_retry(attempt, &error) => {
+                    let delay = policy.delay(attempt);
+                    timer::timeout(delay)
+                        .map(move |_| (state, attempt, policy, next))
+                        .then(asyn!(s, _ => {
+                            let (state, attempt, policy, next) = s.value;
+                            self::attempt(state, attempt + 1, policy, next)
+                        }))
+                        .into()
+                }
+                result => PromiseResult::Resolve(state, Retried { result, attempts: attempt }),
+            }
+        }))
+}
+
+/// Uniformly distributed value in `0..1` range
+fn random() -> f32 {
+    // every `RandomState` is seeded with the new keys
+    let hash = RandomState::new().build_hasher().finish();
+    (hash >> 40) as f32 / (1u64 << 24) as f32
+}
@@ -0,0 +1,150 @@
+//! Schedule configuration for promise processing
+//!
+//! Systems resolving promises run inside [`PromiseSet`] of the [`PromiseSchedule`], which is
+//! [`Update`] by default. Single chain links can be moved to another schedule with
+//! [`in_schedule()`][Promise::in_schedule]:
+//! ```ignore
+//! fn setup(mut commands: Commands) {
+//!     commands.add(
+//!         asyn::timeout(1.)
+//!             // the next step runs in `FixedUpdate` alongside physics
+//!             .in_schedule(FixedUpdate)
+//!             .then(asyn!(_, mut bodies: Query<&mut Velocity> => {
+//!                 bodies.iter_mut().for_each(|mut v| v.0 = Vec2::ZERO);
+//!             })),
+//!     );
+//! }
+//! ```
+use std::collections::BTreeMap;
+
+use bevy::ecs::schedule::{InternedScheduleLabel, ScheduleLabel};
+
+use super::*;
+
+/// System set of the systems resolving promises, configure it to
+/// order promise processing relative to other systems.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct PromiseSet;
+
+/// Part of the [`PromiseSet`] polling coroutines. It runs after the other promise systems
+/// and their commands are applied, so coroutines see the promises resolved in the same frame.
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CoroutineSet;
+
+/// Schedule the promise systems are added to, [`Update`] by default.
+/// Should be inserted before the promise plugins are built.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct PromiseSchedule(pub InternedScheduleLabel);
+impl Default for PromiseSchedule {
+    fn default() -> Self {
+        PromiseSchedule(Update.intern())
+    }
+}
+impl PromiseSchedule {
+    /// Adds `systems` to the [`PromiseSet`] of the configured schedule, before the [`CoroutineSet`]
+    pub fn add_systems<M>(app: &mut App, systems: impl IntoSystemConfigs<M>) {
+        let schedule = app.world.get_resource::<PromiseSchedule>().copied().unwrap_or_default();
+        app.add_systems(schedule.0, systems.in_set(PromiseSet).before(CoroutineSet));
+    }
+}
+
+impl<S: 'static + Send, R: 'static + Send> Promise<S, R> {
+    /// Queues the result of the promise until the `schedule` runs, so the rest of the
+    /// chain runs in that schedule. `PecsPlugin` flushes the queued results in [`First`],
+    /// [`PreUpdate`], [`Update`], [`PostUpdate`], [`Last`] and [`FixedUpdate`]. Other
+    /// schedules should run [`flush_scheduled_promises()`] system.
+    #[track_caller]
+    pub fn in_schedule(self, schedule: impl ScheduleLabel) -> Promise<S, R> {
+        let schedule = schedule.intern();
+        let label = self.label.clone();
+        let time_scale = self.time_scale;
+        let mut promise =
+            self.chain(move |_world, _id, state, result| PromiseResult::Await(scheduled(schedule, state, result)));
+        promise.label = label;
+        promise.time_scale = time_scale;
+        promise
+    }
+}
+
+impl<'w, 's, 'a, S: 'static + Send, R: 'static + Send> PromiseChain<'w, 's, 'a, S, R> {
+    /// Moves the rest of the chain to the `schedule`, see [`Promise::in_schedule()`]
+    pub fn in_schedule(mut self, schedule: impl ScheduleLabel) -> Self {
+        self.promise = self.promise.take().map(|p| p.in_schedule(schedule));
+        self
+    }
+}
+
+#[track_caller]
+fn scheduled<S: 'static + Send, R: 'static + Send>(
+    schedule: InternedScheduleLabel,
+    state: S,
+    result: R,
+) -> Promise<S, R> {
+    Promise::register(
+        move |world, id| {
+            if !world.contains_non_send::<ScheduledPromises>() {
+                world.insert_non_send_resource(ScheduledPromises::default());
+            }
+            let resolve: ScheduledResolve = Box::new(move |world| promise_resolve::<S, R>(world, id, state, result));
+            world
+                .non_send_resource_mut::<ScheduledPromises>()
+                .0
+                .entry(schedule)
+                .or_default()
+                .insert(id, resolve);
+        },
+        move |world, id| {
+            if let Some(mut scheduled) = world.get_non_send_resource_mut::<ScheduledPromises>() {
+                if let Some(queue) = scheduled.0.get_mut(&schedule) {
+                    queue.remove(&id);
+                }
+            }
+        },
+    )
+    .awaits(PromiseAwaits::Schedule)
+}
+
+type ScheduledResolve = Box<dyn FnOnce(&mut World)>;
+
+/// Results of the [`in_schedule()`][Promise::in_schedule] promises waiting for their schedules
+#[derive(Default)]
+struct ScheduledPromises(HashMap<InternedScheduleLabel, BTreeMap<PromiseId, ScheduledResolve>>);
+
+/// Creates system resolving promises queued for the `schedule` with [`Promise::in_schedule()`].
+/// The system should be added to the same `schedule`.
+pub fn flush_scheduled_promises(schedule: impl ScheduleLabel) -> impl FnMut(&mut World) + Send + Sync + 'static {
+    let schedule = schedule.intern();
+    move |world| {
+        let Some(queue) = world
+            .get_non_send_resource::<ScheduledPromises>()
+            .and_then(|scheduled| scheduled.0.get(&schedule))
+        else {
+            return;
+        };
+        // promises queued by the resolved ones wait for the next run
+        let pending: Vec<_> = queue.keys().copied().collect();
+        for id in pending {
+            // resolved promises may discard the queued ones
+            let resolve = world
+                .non_send_resource_mut::<ScheduledPromises>()
+                .0
+                .get_mut(&schedule)
+                .and_then(|queue| queue.remove(&id));
+            if let Some(resolve) = resolve {
+                resolve(world);
+            }
+        }
+    }
+}
+
+pub struct PromiseSchedulePlugin;
+impl Plugin for PromiseSchedulePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(First, flush_scheduled_promises(First));
+        app.add_systems(PreUpdate, flush_scheduled_promises(PreUpdate));
+        app.add_systems(Update, flush_scheduled_promises(Update));
+        app.add_systems(PostUpdate, flush_scheduled_promises(PostUpdate));
+        app.add_systems(Last, flush_scheduled_promises(Last));
+        app.add_systems(FixedUpdate, flush_scheduled_promises(FixedUpdate));
+    }
+}
@@ -0,0 +1,155 @@
+//! Integration with Bevy [`States`]: state-scoped chains and transition promises
+use bevy::ecs::schedule::apply_state_transition;
+
+use super::*;
+
+pub mod asyn {
+    use super::{NextStateWaiters, Promise, PromiseAwaits};
+    use bevy::prelude::*;
+
+    /// Creates promise that requests transition to the `next` state and resolves
+    /// after the [`OnEnter(next)`][OnEnter] schedule has run. If the `next` state
+    /// is already active, resolves without running any transition schedules.
+    #[track_caller]
+    pub fn set<S: States>(next: S) -> Promise<(), ()> {
+        Promise::register(
+            move |world, id| {
+                world.resource_mut::<NextState<S>>().set(next.clone());
+                world.resource_mut::<NextStateWaiters<S>>().insert(id, next);
+            },
+            move |world, id| {
+                world.resource_mut::<NextStateWaiters<S>>().remove(&id);
+            },
+        )
+        .awaits(PromiseAwaits::State)
+    }
+}
+
+/// Adds state-scoped promises and [`asyn::set()`] support for the `S` state.
+/// The state should be registered with [`App::add_state()`] before.
+pub struct PromiseStatePlugin<S: States>(PhantomData<S>);
+impl<S: States> Default for PromiseStatePlugin<S> {
+    fn default() -> Self {
+        PromiseStatePlugin(PhantomData)
+    }
+}
+impl<S: States> Plugin for PromiseStatePlugin<S> {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<StateScopedPromises<S>>();
+        app.init_resource::<NextStateWaiters<S>>();
+        app.add_systems(
+            StateTransition,
+            (
+                discard_state_scoped_promises::<S>.before(apply_state_transition::<S>),
+                resolve_next_state_waiters::<S>.after(apply_state_transition::<S>),
+            ),
+        );
+    }
+}
+
+/// Promises scoped to the state value via [`Promise::in_state()`].
+#[derive(Resource, Deref, DerefMut)]
+pub struct StateScopedPromises<S: States>(HashMap<S, Vec<PromiseHandle>>);
+impl<S: States> Default for StateScopedPromises<S> {
+    fn default() -> Self {
+        StateScopedPromises(HashMap::new())
+    }
+}
+
+/// Promises created by [`asyn::set()`] waiting for the state to be entered.
+#[derive(Resource, Deref, DerefMut)]
+pub struct NextStateWaiters<S: States>(HashMap<PromiseId, S>);
+impl<S: States> Default for NextStateWaiters<S> {
+    fn default() -> Self {
+        NextStateWaiters(HashMap::new())
+    }
+}
+
+impl<S: 'static + Send, R: 'static + Send> Promise<S, R> {
+    /// Limits the chain lifetime to the `state`: the chain is discarded right before
+    /// [`OnExit(state)`][OnExit] runs. Chains registered while the `state` is not active
+    /// are discarded immediately.
+    /// ```ignore
+    /// fn start_level(mut commands: Commands) {
+    ///     commands.add(
+    ///         asyn::timeout(30.)
+    ///             .then(asyn!(_ => info!("Hurry up!")))
+    ///             .in_state(GameState::Playing),
+    ///     );
+    /// }
+    /// ```
+    pub fn in_state<St: States>(self, state: St) -> Promise<S, R> {
+        self.scoped(move |world, handle| {
+            if world
+                .get_resource::<State<St>>()
+                .map(|s| s.get() != &state)
+                .unwrap_or(true)
+            {
+                handle.cancel();
+                return;
+            }
+            let mut scoped = world.resource_mut::<StateScopedPromises<St>>();
+            let handles = scoped.entry(state).or_default();
+            handles.retain(PromiseHandle::is_pending);
+            handles.push(handle);
+        })
+    }
+}
+
+impl<'w, 's, 'a, S: 'static + Send, R: 'static + Send> PromiseChain<'w, 's, 'a, S, R> {
+    /// Limits the chain lifetime to the `state`, see [`Promise::in_state()`]
+    pub fn in_state<St: States>(mut self, state: St) -> Self {
+        self.promise = self.promise.take().map(|p| p.in_state(state));
+        self
+    }
+}
+
+pub struct StatefulAsynStates<S>(S);
+impl<S: 'static + Send> StatefulAsynStates<S> {
+    /// Stateful version of [`asyn::set()`]
+    #[track_caller]
+    pub fn set<St: States>(self, next: St) -> Promise<S, ()> {
+        asyn::set(next).with(self.0)
+    }
+}
+
+pub trait StateOpsExtension<S> {
+    fn states(self) -> StatefulAsynStates<S>;
+}
+impl<S: 'static + Send> StateOpsExtension<S> for AsynOps<S> {
+    fn states(self) -> StatefulAsynStates<S> {
+        StatefulAsynStates(self.0)
+    }
+}
+
+fn discard_state_scoped_promises<S: States>(world: &mut World) {
+    let Some(next) = world.resource::<NextState<S>>().0.clone() else {
+        return;
+    };
+    let current = world.resource::<State<S>>().get().clone();
+    if next == current {
+        return;
+    }
+    let handles = world
+        .resource_mut::<StateScopedPromises<S>>()
+        .remove(&current)
+        .unwrap_or_default();
+    for handle in handles {
+        handle.discard(world);
+    }
+}
+
+fn resolve_next_state_waiters<S: States>(
+    mut commands: Commands,
+    state: Res<State<S>>,
+    mut waiters: ResMut<NextStateWaiters<S>>,
+) {
+    waiters.retain(|id, next| {
+        if state.get() == next {
+            commands.promise(*id).resolve(());
+            false
+        } else {
+            true
+        }
+    });
+}
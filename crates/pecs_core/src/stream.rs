@@ -0,0 +1,419 @@
+//! Streams of values delivered over time
+//!
+//! [`PromiseStream`] is the promise resolving many times. Values are pushed by the
+//! [`StreamSender`] from any system and consumed with [`next()`][PromiseStream::next],
+//! [`for_each()`][PromiseStream::for_each] or [`fold()`][PromiseStream::fold]:
+//! ```ignore
+//! #[derive(Resource)]
+//! struct Presses(StreamSender<Entity>);
+//!
+//! fn setup(mut commands: Commands) {
+//!     let (sender, presses) = PromiseStream::channel();
+//!     commands.insert_resource(Presses(sender));
+//!     commands.add(
+//!         presses
+//!             .with(0)
+//!             .take(3)
+//!             .for_each(asyn!(s, entity => {
+//!                 s.value += 1;
+//!                 info!("#{} press of {entity:?}", s.value);
+//!                 // the next value is awaited after the returned promise resolves
+//!                 s.asyn().timeout(0.5)
+//!             }))
+//!             .then(asyn!(s, _ => info!("Done after {} presses", s.value))),
+//!     );
+//! }
+//!
+//! fn send_presses(presses: Res<Presses>, buttons: Query<(Entity, &Interaction), Changed<Interaction>>) {
+//!     for (entity, interaction) in buttons.iter() {
+//!         if *interaction == Interaction::Pressed {
+//!             presses.0.send(entity);
+//!         }
+//!     }
+//! }
+//! ```
+use super::*;
+
+pub(crate) type Pull<T> = Box<dyn FnMut() -> Promise<(), Option<T>> + Send>;
+
+/// Sequence of `T` values with the `S` state. Every combinator consumes the stream,
+/// the state passes to the promise returned by [`for_each()`][PromiseStream::for_each]
+/// or [`fold()`][PromiseStream::fold]. Dropping or discarding the consumer closes the
+/// stream, so [`StreamSender::send()`] starts returning `false`.
+pub struct PromiseStream<S, T> {
+    state: S,
+    pull: Pull<T>,
+}
+
+impl<T: 'static + Send + Sync> PromiseStream<(), T> {
+    /// Creates the [`StreamSender`] and the stream receiving values it sends. The stream
+    /// ends after every sender is dropped or [closed][StreamSender::close] and the sent
+    /// values are consumed.
+    pub fn channel() -> (StreamSender<T>, PromiseStream<(), T>) {
+        let (sender, receiver) = channel::channel();
+        (StreamSender(sender), receiver.into_stream())
+    }
+}
+
+impl<T: 'static + Send> PromiseStream<(), T> {
+    pub(crate) fn from_pull(pull: Pull<T>) -> PromiseStream<(), T> {
+        PromiseStream { state: (), pull }
+    }
+}
+
+impl<S: 'static + Send, T: 'static + Send> PromiseStream<S, T> {
+    /// Create new [`PromiseStream<S2, T>`] with state replaced with `S2`
+    pub fn with<S2: 'static + Send>(self, state: S2) -> PromiseStream<S2, T> {
+        PromiseStream { state, pull: self.pull }
+    }
+
+    /// Create new [`Promise`] resolving with the next value, or with `None` if the stream
+    /// is ended. The stream itself becomes the state of the promise, so it can be consumed further.
+    #[track_caller]
+    pub fn next(mut self) -> Promise<PromiseStream<S, T>, Option<T>> {
+        (self.pull)().map(move |_| self)
+    }
+
+    /// Calls `func` for every value of the stream. The next value is awaited after the promise
+    /// returned by `func` resolves. The returned promise resolves with the state when the stream ends.
+    #[track_caller]
+    pub fn for_each<O: 'static + Into<PromiseResult<S, ()>>, P: PromiseParams>(
+        self,
+        func: Asyn<(PromiseState<S>, T), O, P>,
+    ) -> Promise<S, ()> {
+        for_each(self.state, self.pull, func)
+    }
+
+    /// Reduces the stream with `func` starting with `init`
+    #[track_caller]
+    pub fn fold<A: 'static + Send, F: 'static + Send + FnMut(A, T) -> A>(self, init: A, func: F) -> Promise<S, A> {
+        fold(self.state, self.pull, init, func)
+    }
+
+    /// Collects every value of the stream
+    #[track_caller]
+    pub fn collect(self) -> Promise<S, Vec<T>> {
+        self.fold(vec![], |mut values, value| {
+            values.push(value);
+            values
+        })
+    }
+
+    /// Create new [`PromiseStream<S, T2>`] with values mapped by `map` from `T` to `T2`
+    pub fn map<T2: 'static + Send, F: 'static + Send + FnMut(T) -> T2>(mut self, map: F) -> PromiseStream<S, T2> {
+        let map = Arc::new(Mutex::new(map));
+        PromiseStream {
+            state: self.state,
+            pull: Box::new(move || {
+                let map = map.clone();
+                (self.pull)().map_result(move |value| value.map(|value| (map.lock().unwrap())(value)))
+            }),
+        }
+    }
+
+    /// Create new [`PromiseStream<S, T>`] that skips values `predicate` returns `false` for
+    pub fn filter<F: 'static + Send + FnMut(&T) -> bool>(self, predicate: F) -> PromiseStream<S, T> {
+        let pull = Arc::new(Mutex::new(self.pull));
+        let predicate = Arc::new(Mutex::new(predicate));
+        PromiseStream {
+            state: self.state,
+            pull: Box::new(move || filter(pull.clone(), predicate.clone())),
+        }
+    }
+
+    /// Create new [`PromiseStream<S, T>`] that ends after `count` values. The source
+    /// is dropped after the last value, so its senders get closed.
+    pub fn take(self, count: usize) -> PromiseStream<S, T> {
+        let mut source = (count > 0).then_some(self.pull);
+        let mut remaining = count;
+        PromiseStream {
+            state: self.state,
+            pull: Box::new(move || {
+                let Some(pull) = &mut source else {
+                    return Promise::from(()).with_result(None);
+                };
+                let next = pull();
+                remaining -= 1;
+                if remaining == 0 {
+                    source = None;
+                }
+                next
+            }),
+        }
+    }
+
+    /// Create new [`PromiseStream<S, T>`] that ends when `promise` resolves. The `promise`
+    /// is registered with the first pull and cancelled if the stream is dropped before.
+    pub fn until<S2: 'static + Send, R2: 'static + Send>(mut self, promise: Promise<S2, R2>) -> PromiseStream<S, T> {
+        let mut stop = promise.map(|_| ()).with_result(());
+        let until = Arc::new(Mutex::new(Until {
+            ended: false,
+            pending: None,
+            handle: stop.handle(),
+            stop: None,
+        }));
+        // the stop promise doesn't keep the stream alive
+        let stopped = Arc::downgrade(&until);
+        stop.resolve = Some(Box::new(move |world, _, _| {
+            let Some(stopped) = stopped.upgrade() else {
+                return;
+            };
+            let pending = {
+                let mut until = stopped.lock().unwrap();
+                until.ended = true;
+                until.pending.take()
+            };
+            if let Some((outer, inner)) = pending {
+                promise_discard::<(), Option<T>>(world, inner);
+                promise_resolve::<(), Option<T>>(world, outer, (), None);
+            }
+        }));
+        until.lock().unwrap().stop = Some(stop);
+        PromiseStream {
+            state: self.state,
+            pull: Box::new(move || {
+                if until.lock().unwrap().ended {
+                    return Promise::from(()).with_result(None);
+                }
+                let mut inner = (self.pull)();
+                let inner_id = inner.id;
+                let register = until.clone();
+                let discard = until.clone();
+                Promise::register(
+                    move |world, id| {
+                        let stop = register.lock().unwrap().stop.take();
+                        if let Some(stop) = stop {
+                            promise_register(world, stop);
+                        }
+                        if register.lock().unwrap().ended {
+                            promise_resolve::<(), Option<T>>(world, id, (), None);
+                            return;
+                        }
+                        register.lock().unwrap().pending = Some((id, inner_id));
+                        let resolved = register.clone();
+                        inner.resolve = Some(Box::new(move |world, _, value| {
+                            resolved.lock().unwrap().pending = None;
+                            promise_resolve::<(), Option<T>>(world, id, (), value);
+                        }));
+                        promise_register(world, inner);
+                    },
+                    move |world, _| {
+                        let pending = discard.lock().unwrap().pending.take();
+                        if let Some((_, inner)) = pending {
+                            promise_discard::<(), Option<T>>(world, inner);
+                        }
+                    },
+                )
+                .awaits(PromiseAwaits::Chain)
+            }),
+        }
+    }
+}
+
+/// Sends values to the [`PromiseStream`] created with [`PromiseStream::channel()`].
+/// Can be cloned and stored in resources or components.
+pub struct StreamSender<T>(channel::Sender<T>);
+
+impl<T: 'static + Send + Sync> StreamSender<T> {
+    /// Pushes `value` to the stream. Returns `false` if the stream is closed or dropped.
+    pub fn send(&self, value: T) -> bool {
+        self.0.try_send(value).is_ok()
+    }
+    /// Ends the stream after the sent values are consumed
+    pub fn close(&self) {
+        self.0.close()
+    }
+    /// Returns `true` if the stream is closed or dropped
+    pub fn is_closed(&self) -> bool {
+        self.0.is_closed()
+    }
+}
+
+impl<T> Clone for StreamSender<T> {
+    fn clone(&self) -> Self {
+        StreamSender(self.0.clone())
+    }
+}
+
+struct Until {
+    ended: bool,
+    /// pull waiting for the value and the pull of the source stream it awaits
+    pending: Option<(PromiseId, PromiseId)>,
+    handle: PromiseHandle,
+    stop: Option<Promise<(), ()>>,
+}
+impl Drop for Until {
+    fn drop(&mut self) {
+        // the stream is dropped before the stop promise was registered
+        diagnostic::drop_quietly(self.stop.take());
+        self.handle.cancel();
+    }
+}
+
+/// What the [`Driver`] does after the value is pulled
+enum Step<A> {
+    /// Pulls the next value after the promise resolves
+    Await(Promise<A, ()>),
+    /// Pulls the next value right away
+    Pull(A),
+    /// Resolves the driver
+    Done(A),
+}
+
+/// Promise of the driver in flight
+enum Pending {
+    Pull(PromiseId),
+    Step(PromiseId),
+}
+
+/// Result of the pending promise, resolved while the driver was pumping
+enum Ready<T, A> {
+    Pulled(Option<T>),
+    Stepped(A),
+}
+
+type StepFn<T, A> = Box<dyn FnMut(A, Option<T>) -> Step<A> + Send>;
+
+/// Progress of the single stream consumer, lives in the world until the consumer
+/// resolves or gets discarded.
+struct Driver<T, A> {
+    pull: Pull<T>,
+    step: StepFn<T, A>,
+    acc: Option<A>,
+    pending: Option<Pending>,
+    ready: Option<Ready<T, A>>,
+    pumping: bool,
+}
+
+/// Pulls values of the stream one by one passing them to `step` with `acc`, until
+/// `step` returns [`Step::Done`]. Values pulled synchronously are handled by the loop
+/// in [`pump()`], so long streams don't grow the stack or the promise chain.
+#[track_caller]
+fn drive<T: 'static + Send, A: 'static + Send>(
+    acc: A,
+    pull: Pull<T>,
+    step: impl 'static + Send + FnMut(A, Option<T>) -> Step<A>,
+) -> Promise<A, ()> {
+    Promise::register(
+        move |world, id| {
+            Combined::insert(
+                world,
+                id,
+                Driver {
+                    pull,
+                    step: Box::new(step) as StepFn<T, A>,
+                    acc: Some(acc),
+                    pending: None,
+                    ready: None,
+                    pumping: false,
+                },
+            );
+            pump::<T, A>(world, id);
+        },
+        move |world, id| {
+            let Some(driver) = Combined::<Driver<T, A>>::remove(world, id) else {
+                return;
+            };
+            match driver.pending {
+                Some(Pending::Pull(pull)) => promise_discard::<(), Option<T>>(world, pull),
+                Some(Pending::Step(step)) => promise_discard::<A, ()>(world, step),
+                None => {}
+            }
+        },
+    )
+    .awaits(PromiseAwaits::Stream)
+}
+
+fn pump<T: 'static + Send, A: 'static + Send>(world: &mut World, id: PromiseId) {
+    match Combined::<Driver<T, A>>::get_mut(world, id) {
+        Some(driver) if !driver.pumping => driver.pumping = true,
+        _ => return,
+    }
+    loop {
+        let Some(driver) = Combined::<Driver<T, A>>::get_mut(world, id) else {
+            return;
+        };
+        let value = match driver.ready.take() {
+            None if driver.pending.is_some() => break,
+            None => {
+                let mut pull = (driver.pull)();
+                driver.pending = Some(Pending::Pull(pull.id));
+                pull.resolve = Some(Box::new(move |world, _, value| {
+                    ready::<T, A>(world, id, Ready::Pulled(value));
+                }));
+                promise_register(world, pull);
+                continue;
+            }
+            Some(Ready::Stepped(acc)) => {
+                driver.acc = Some(acc);
+                continue;
+            }
+            Some(Ready::Pulled(value)) => value,
+        };
+        let acc = driver.acc.take().unwrap();
+        match (driver.step)(acc, value) {
+            Step::Pull(acc) => driver.acc = Some(acc),
+            Step::Done(acc) => {
+                Combined::<Driver<T, A>>::remove(world, id);
+                promise_resolve::<A, ()>(world, id, acc, ());
+                return;
+            }
+            Step::Await(mut step) => {
+                driver.pending = Some(Pending::Step(step.id));
+                step.resolve = Some(Box::new(move |world, acc, _| {
+                    ready::<T, A>(world, id, Ready::Stepped(acc));
+                }));
+                promise_register(world, step);
+            }
+        }
+    }
+    if let Some(driver) = Combined::<Driver<T, A>>::get_mut(world, id) {
+        driver.pumping = false;
+    }
+}
+
+/// Records the result of the pending promise and continues the driver
+fn ready<T: 'static + Send, A: 'static + Send>(world: &mut World, id: PromiseId, ready: Ready<T, A>) {
+    let Some(driver) = Combined::<Driver<T, A>>::get_mut(world, id) else {
+        return;
+    };
+    driver.pending = None;
+    driver.ready = Some(ready);
+    pump::<T, A>(world, id);
+}
+
+fn filter<T: 'static + Send, F: 'static + Send + FnMut(&T) -> bool>(
+    pull: Arc<Mutex<Pull<T>>>,
+    predicate: Arc<Mutex<F>>,
+) -> Promise<(), Option<T>> {
+    let source: Pull<T> = Box::new(move || (pull.lock().unwrap())());
+    drive(None, source, move |_, value| match value {
+        Some(value) if !(predicate.lock().unwrap())(&value) => Step::Pull(None),
+        value => Step::Done(value),
+    })
+    .chain(|_world, _id, value, _| PromiseResult::Resolve((), value))
+}
+
+fn for_each<S: 'static + Send, T: 'static + Send, O: 'static + Into<PromiseResult<S, ()>>, P: PromiseParams>(
+    state: S,
+    pull: Pull<T>,
+    func: Asyn<(PromiseState<S>, T), O, P>,
+) -> Promise<S, ()> {
+    drive(state, pull, move |state, value| match value {
+        Some(value) => Step::Await(Promise::from(state).with_result(value).then(func.clone())),
+        None => Step::Done(state),
+    })
+}
+
+fn fold<S: 'static + Send, T: 'static + Send, A: 'static + Send, F: 'static + Send + FnMut(A, T) -> A>(
+    state: S,
+    pull: Pull<T>,
+    acc: A,
+    mut func: F,
+) -> Promise<S, A> {
+    drive((state, acc), pull, move |(state, acc), value| match value {
+        Some(value) => Step::Pull((state, func(acc, value))),
+        None => Step::Done((state, acc)),
+    })
+    .chain(|_world, _id, (state, acc), _| PromiseResult::Resolve(state, acc))
+}
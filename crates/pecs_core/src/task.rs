@@ -0,0 +1,158 @@
+//! Run futures and heavy computations on Bevy task pools as promises
+use bevy::tasks::{AsyncComputeTaskPool, IoTaskPool, TaskPool};
+use std::future::Future;
+
+use super::*;
+
+pub mod asyn {
+    use super::*;
+
+    /// Creates promise that runs the `future` on the [`AsyncComputeTaskPool`]
+    /// and resolves with its output. The task is cancelled when the promise
+    /// is discarded.
+    /// ```ignore
+    /// commands.add(
+    ///     asyn::task::spawn(async move { find_path(from, to).await })
+    ///         .then(asyn!(_, path => {
+    ///             info!("Path found: {path:?}");
+    ///         })),
+    /// );
+    /// ```
+    #[track_caller]
+    pub fn spawn<T: 'static + Send + Sync, F: 'static + Send + Future<Output = T>>(future: F) -> Promise<(), T> {
+        spawn_on(async_compute_pool, future)
+    }
+
+    /// Creates promise that runs the `func` on the [`AsyncComputeTaskPool`]
+    /// and resolves with its result.
+    /// ```ignore
+    /// commands.add(
+    ///     asyn::task::compute(move || generate_chunk(seed))
+    ///         .then(asyn!(_, chunk, mut commands: Commands => {
+    ///             commands.spawn(chunk);
+    ///         })),
+    /// );
+    /// ```
+    #[track_caller]
+    pub fn compute<T: 'static + Send + Sync, F: 'static + Send + FnOnce() -> T>(func: F) -> Promise<(), T> {
+        spawn_on(async_compute_pool, async move { func() })
+    }
+
+    /// Creates promise that runs the `future` on the [`IoTaskPool`]
+    /// and resolves with its output.
+    #[track_caller]
+    pub fn io<T: 'static + Send + Sync, F: 'static + Send + Future<Output = T>>(future: F) -> Promise<(), T> {
+        spawn_on(io_pool, future)
+    }
+}
+
+pub struct PromiseTaskPlugin;
+impl Plugin for PromiseTaskPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tasks>();
+        PromiseSchedule::add_systems(app, process_tasks);
+    }
+}
+
+pub struct StatefulAsynTask<S>(S);
+impl<S: 'static + Send> StatefulAsynTask<S> {
+    /// Stateful version of [`asyn::spawn()`]
+    #[track_caller]
+    pub fn spawn<T: 'static + Send + Sync, F: 'static + Send + Future<Output = T>>(self, future: F) -> Promise<S, T> {
+        asyn::spawn(future).with(self.0)
+    }
+    /// Stateful version of [`asyn::compute()`]
+    #[track_caller]
+    pub fn compute<T: 'static + Send + Sync, F: 'static + Send + FnOnce() -> T>(self, func: F) -> Promise<S, T> {
+        asyn::compute(func).with(self.0)
+    }
+    /// Stateful version of [`asyn::io()`]
+    #[track_caller]
+    pub fn io<T: 'static + Send + Sync, F: 'static + Send + Future<Output = T>>(self, future: F) -> Promise<S, T> {
+        asyn::io(future).with(self.0)
+    }
+}
+
+pub trait TaskOpsExtension<S> {
+    fn task(self) -> StatefulAsynTask<S>;
+}
+impl<S: 'static + Send> TaskOpsExtension<S> for AsynOps<S> {
+    fn task(self) -> StatefulAsynTask<S> {
+        StatefulAsynTask(self.0)
+    }
+}
+
+type TaskPoll = Box<dyn FnMut(PromiseId, &mut Commands) -> bool + Send + Sync>;
+
+/// Running tasks, polled every frame by [`process_tasks`].
+#[derive(Resource, Deref, DerefMut, Default)]
+pub struct Tasks(HashMap<PromiseId, TaskPoll>);
+
+pub fn process_tasks(mut tasks: ResMut<Tasks>, mut commands: Commands) {
+    tasks.retain(|promise, poll| !poll(*promise, &mut commands));
+}
+
+fn async_compute_pool() -> &'static TaskPool {
+    AsyncComputeTaskPool::get()
+}
+
+fn io_pool() -> &'static TaskPool {
+    IoTaskPool::get()
+}
+
+#[track_caller]
+fn spawn_on<T: 'static + Send + Sync, F: 'static + Send + Future<Output = T>>(
+    pool: fn() -> &'static TaskPool,
+    future: F,
+) -> Promise<(), T> {
+    Promise::register(
+        move |world, id| {
+            let poll = spawn_task(pool(), future);
+            world.resource_mut::<Tasks>().insert(id, poll);
+        },
+        move |world, id| {
+            // dropping the task cancels it
+            world.resource_mut::<Tasks>().remove(&id);
+        },
+    )
+    .awaits(PromiseAwaits::Task)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn spawn_task<T: 'static + Send + Sync, F: 'static + Send + Future<Output = T>>(
+    pool: &TaskPool,
+    future: F,
+) -> TaskPoll {
+    use futures_lite::future;
+    let mut task = pool.spawn(future);
+    Box::new(move |id, commands| {
+        if let Some(result) = future::block_on(future::poll_once(&mut task)) {
+            commands.add(PromiseCommand::resolve(id, result));
+            true
+        } else {
+            false
+        }
+    })
+}
+
+#[cfg(target_arch = "wasm32")]
+fn spawn_task<T: 'static + Send + Sync, F: 'static + Send + Future<Output = T>>(
+    pool: &TaskPool,
+    future: F,
+) -> TaskPoll {
+    // tasks can't be polled on wasm, the output is passed through the slot instead
+    let slot = Arc::new(Mutex::new(None));
+    let output = slot.clone();
+    pool.spawn(async move {
+        let result = future.await;
+        *output.lock().unwrap() = Some(result);
+    });
+    Box::new(move |id, commands| {
+        if let Some(result) = slot.lock().unwrap().take() {
+            commands.add(PromiseCommand::resolve(id, result));
+            true
+        } else {
+            false
+        }
+    })
+}
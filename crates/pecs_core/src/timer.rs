@@ -1,37 +1,341 @@
 //! Defers promise resolving for a fixed amount of time
+//!
+//! Timers are measured with the [`TimerClock`], [`Time<Virtual>`] by default, so they
+//! freeze while the game is paused. [`PromiseTimer`] picks another clock, and
+//! [`time_scale()`][Promise::time_scale] speeds up or slows down every timer of the chain:
+//! ```ignore
+//! fn setup(mut commands: Commands) {
+//!     commands.add(
+//!         PromiseTimer::real_time()
+//!             .timeout(Duration::from_secs(2))
+//!             .then(asyn!(_ => asyn::timeout(1.0)))
+//!             .then(asyn!(_ => info!("Done in 1.5 seconds")))
+//!             .time_scale(2.0),
+//!     );
+//!     commands.add(
+//!         asyn::timer::interval(0.5)
+//!             .take(3)
+//!             .for_each(asyn!(_, tick => info!("Tick #{tick}"))),
+//!     );
+//! }
+//! ```
+use std::{cmp::Reverse, collections::BinaryHeap, time::Duration};
+
 use super::*;
-pub fn timeout(duration: f32) -> Promise<(), ()> {
+
+thread_local!(static CHAIN_SCALE: RefCell<Vec<f64>> = const { RefCell::new(vec![]) });
+
+pub mod asyn {
+    pub use super::{interval, timeout, PromiseTimer};
+}
+
+/// Creates promise that resolves after `duration` of [`Time<Virtual>`] passed
+#[track_caller]
+pub fn timeout(duration: impl IntoDuration) -> Promise<(), ()> {
+    PromiseTimer::virtual_time().timeout(duration)
+}
+
+/// Creates infinite [`PromiseStream`][stream::PromiseStream] producing the number of the tick
+/// every `period` of [`Time<Virtual>`], see [`PromiseTimer::interval()`]
+pub fn interval(period: impl IntoDuration) -> stream::PromiseStream<(), u64> {
+    PromiseTimer::virtual_time().interval(period)
+}
+
+pub trait TimerOpsExtension<S> {
+    fn timeout(self, duration: impl IntoDuration) -> Promise<S, ()>;
+    fn interval(self, period: impl IntoDuration) -> stream::PromiseStream<S, u64>;
+}
+impl<S: 'static + Send> TimerOpsExtension<S> for AsynOps<S> {
+    #[track_caller]
+    fn timeout(self, duration: impl IntoDuration) -> Promise<S, ()> {
+        timeout(duration).map(|_| self.0)
+    }
+    fn interval(self, period: impl IntoDuration) -> stream::PromiseStream<S, u64> {
+        interval(period).with(self.0)
+    }
+}
+
+/// Time passed in seconds (`f32` or `f64`) or as [`Duration`]. Negative and `NaN` seconds
+/// are treated as zero.
+pub trait IntoDuration {
+    fn into_duration(self) -> Duration;
+}
+impl IntoDuration for Duration {
+    fn into_duration(self) -> Duration {
+        self
+    }
+}
+impl IntoDuration for f32 {
+    fn into_duration(self) -> Duration {
+        (self as f64).into_duration()
+    }
+}
+impl IntoDuration for f64 {
+    fn into_duration(self) -> Duration {
+        if self > 0. {
+            Duration::try_from_secs_f64(self).unwrap_or(Duration::MAX)
+        } else {
+            Duration::ZERO
+        }
+    }
+}
+
+/// Resource timers are measured with. Implemented for every [`Time`] clock,
+/// user clocks should implement it to be used with [`PromiseTimer::new()`].
+pub trait TimerClock: Resource {
+    /// Time passed since the clock started
+    fn elapsed(&self) -> Duration;
+}
+impl<T: Default + Send + Sync + 'static> TimerClock for Time<T> {
+    fn elapsed(&self) -> Duration {
+        Time::elapsed(self)
+    }
+}
+
+/// Creates timers measured with the `C` clock
+pub struct PromiseTimer<C: TimerClock = Time<Virtual>> {
+    clock: PhantomData<fn() -> C>,
+}
+
+impl<C: TimerClock> Clone for PromiseTimer<C> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+impl<C: TimerClock> Copy for PromiseTimer<C> {}
+
+impl<C: TimerClock> std::fmt::Debug for PromiseTimer<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PromiseTimer")
+            .field("clock", &std::any::type_name::<C>())
+            .finish()
+    }
+}
+
+impl PromiseTimer<Time<Virtual>> {
+    /// Timer measured with the game time, it freezes when [`Time<Virtual>`] is paused
+    pub fn virtual_time() -> Self {
+        PromiseTimer::new()
+    }
+}
+
+impl PromiseTimer<Time<Real>> {
+    /// Timer measured with the real time, it keeps going when the game is paused
+    pub fn real_time() -> Self {
+        PromiseTimer::new()
+    }
+}
+
+impl<C: TimerClock> PromiseTimer<C> {
+    /// Timer measured with the `C` clock, the clock resource should be inserted
+    /// before the timer promises are registered
+    pub fn new() -> Self {
+        PromiseTimer { clock: PhantomData }
+    }
+
+    /// Creates promise that resolves after `duration` of scaled time passed
+    #[track_caller]
+    pub fn timeout(&self, duration: impl IntoDuration) -> Promise<(), ()> {
+        let duration = duration.into_duration();
+        register_timer::<C>(move |now, scale| now.saturating_add(scaled(duration, scale)))
+    }
+
+    /// Creates infinite [`PromiseStream`][stream::PromiseStream] producing the number of the tick
+    /// every `period` of scaled time. Ticks are counted from the first pulled value, ticks missed
+    /// during long frames are skipped.
+    pub fn interval(&self, period: impl IntoDuration) -> stream::PromiseStream<(), u64> {
+        let period = period.into_duration();
+        let ticks = Arc::new(Mutex::new(Ticks { start: None, tick: 0 }));
+        stream::PromiseStream::from_pull(Box::new(move || {
+            let next = ticks.clone();
+            let tick = ticks.clone();
+            register_timer::<C>(move |now, scale| next.lock().unwrap().next(now, scaled(period, scale)))
+                .map_result(move |_| Some(tick.lock().unwrap().tick))
+                .awaits(PromiseAwaits::Stream)
+        }))
+    }
+}
+
+impl<C: TimerClock> Default for PromiseTimer<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<S: 'static + Send, R: 'static + Send> Promise<S, R> {
+    /// Makes the timers of the chain run `scale` times faster. Promises registered
+    /// inside the chain inherit the scale, so nested [`timeout()`] calls are scaled
+    /// as well. Timers with zero or negative scale never resolve.
+    /// ```ignore
+    /// commands.add(
+    ///     asyn::timeout(1.)
+    ///         .then(asyn!(_ => asyn::timeout(1.)))
+    ///         .then(asyn!(_ => info!("One second passed")))
+    ///         .time_scale(2.),
+    /// );
+    /// ```
+    pub fn time_scale(mut self, scale: f64) -> Promise<S, R> {
+        self.time_scale = Some(scale);
+        self
+    }
+}
+
+impl<'w, 's, 'a, S: 'static + Send, R: 'static + Send> PromiseChain<'w, 's, 'a, S, R> {
+    /// Sets the time scale of the chain, see [`Promise::time_scale()`]
+    pub fn time_scale(mut self, scale: f64) -> Self {
+        self.promise = self.promise.take().map(|p| p.time_scale(scale));
+        self
+    }
+}
+
+/// Time scale of the chain running its callbacks on this thread, `1.` outside of chains
+pub(crate) fn chain_scale() -> f64 {
+    CHAIN_SCALE.with(|scales| scales.borrow().last().copied()).unwrap_or(1.)
+}
+
+/// Leaves the time scale entered with [`enter_scale()`] when dropped
+pub(crate) struct ScaleGuard;
+impl Drop for ScaleGuard {
+    fn drop(&mut self) {
+        CHAIN_SCALE.with(|scales| scales.borrow_mut().pop());
+    }
+}
+
+/// Makes the timers registered until the guard is dropped use the `scale`
+pub(crate) fn enter_scale(scale: f64) -> ScaleGuard {
+    CHAIN_SCALE.with(|scales| scales.borrow_mut().push(scale));
+    ScaleGuard
+}
+
+fn scaled(duration: Duration, scale: f64) -> Duration {
+    if scale == 1. {
+        duration
+    } else if scale > 0. {
+        Duration::try_from_secs_f64(duration.as_secs_f64() / scale).unwrap_or(Duration::MAX)
+    } else {
+        Duration::MAX
+    }
+}
+
+struct Ticks {
+    start: Option<Duration>,
+    tick: u64,
+}
+
+impl Ticks {
+    /// Advances to the next tick after `now` and returns its deadline
+    fn next(&mut self, now: Duration, period: Duration) -> Duration {
+        let start = *self.start.get_or_insert(now);
+        self.tick += 1;
+        if !period.is_zero() {
+            let passed = now.saturating_sub(start).as_nanos() / period.as_nanos();
+            self.tick = self.tick.max(passed as u64 + 1);
+        }
+        let offset = period.as_nanos().saturating_mul(self.tick as u128);
+        let offset = u64::try_from(offset / 1_000_000_000)
+            .map(|secs| Duration::new(secs, (offset % 1_000_000_000) as u32))
+            .unwrap_or(Duration::MAX);
+        start.saturating_add(offset)
+    }
+}
+
+/// Registers the timer resolving at the `deadline` computed from the current `C` time
+/// and the time scale of the chain
+#[track_caller]
+fn register_timer<C: TimerClock>(deadline: impl 'static + Send + FnOnce(Duration, f64) -> Duration) -> Promise<(), ()> {
     Promise::<(), ()>::register(
         move |world, id| {
-            let time = world.resource::<Time>();
-            let end = time.elapsed_seconds() + duration - time.delta_seconds();
-            world.resource_mut::<Timers>().insert(id, end);
+            if !world.contains_resource::<Timers<C>>() {
+                world.init_resource::<Timers<C>>();
+                PromiseResolvers::add(world, process_timers::<C>);
+            }
+            let Some(clock) = world.get_resource::<C>() else {
+                panic!("Clock {} is not inserted into the world", std::any::type_name::<C>());
+            };
+            let end = deadline(clock.elapsed(), chain_scale());
+            world.resource_mut::<Timers<C>>().insert(id, end);
         },
         move |world, id| {
-            world.resource_mut::<Timers>().remove(&id);
+            if let Some(mut timers) = world.get_resource_mut::<Timers<C>>() {
+                timers.remove(id);
+            }
         },
     )
+    .awaits(PromiseAwaits::Timer)
 }
-pub trait TimerOpsExtension<S> {
-    fn timeout(self, duration: f32) -> Promise<S, ()>;
-}
-impl<S: 'static> TimerOpsExtension<S> for AsynOps<S> {
-    fn timeout(self, duration: f32) -> Promise<S, ()> {
-        timeout(duration).map(|_| self.0)
+
+/// Error returned by promises created with [`timeout()`][PromiseLikeTimeout::timeout]
+/// when the deadline passes before the promise resolves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Elapsed;
+
+impl std::fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "deadline has elapsed")
     }
 }
 
-#[derive(Resource, Deref, DerefMut, Default)]
-pub struct Timers(HashMap<PromiseId, f32>);
+impl std::error::Error for Elapsed {}
 
-pub fn process_timers(time: Res<Time>, mut commands: Commands, mut timers: ResMut<Timers>) {
-    let elapsed = time.elapsed_seconds();
-    timers.retain(|promise, end| {
-        if &elapsed >= end {
-            commands.add(PromiseCommand::resolve(*promise, ()));
-            false
-        } else {
-            true
+/// Deadlines of the pending timers measured with the `C` clock. Timers are kept in the
+/// min-heap ordered by deadline, so [`process_timers`] only touches the elapsed ones.
+/// Discarded timers leave tombstones in the heap, skipped when popped and dropped
+/// once they outnumber the pending timers.
+#[derive(Resource)]
+pub struct Timers<C: TimerClock = Time<Virtual>> {
+    deadlines: HashMap<PromiseId, Duration>,
+    queue: BinaryHeap<Reverse<(Duration, PromiseId)>>,
+    clock: PhantomData<fn() -> C>,
+}
+
+impl<C: TimerClock> Default for Timers<C> {
+    fn default() -> Self {
+        Timers {
+            deadlines: HashMap::new(),
+            queue: BinaryHeap::new(),
+            clock: PhantomData,
+        }
+    }
+}
+
+impl<C: TimerClock> Timers<C> {
+    /// Number of pending timers
+    pub fn len(&self) -> usize {
+        self.deadlines.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.deadlines.is_empty()
+    }
+    fn insert(&mut self, promise: PromiseId, deadline: Duration) {
+        self.deadlines.insert(promise, deadline);
+        self.queue.push(Reverse((deadline, promise)));
+    }
+    fn remove(&mut self, promise: PromiseId) {
+        if self.deadlines.remove(&promise).is_some() && self.queue.len() > 2 * self.deadlines.len() + 64 {
+            let deadlines = &self.deadlines;
+            self.queue
+                .retain(|Reverse((_, promise))| deadlines.contains_key(promise));
         }
-    });
+    }
+    /// Pops the next timer with the deadline not later than `elapsed`
+    fn pop_elapsed(&mut self, elapsed: Duration) -> Option<PromiseId> {
+        while let Some(&Reverse((deadline, promise))) = self.queue.peek() {
+            if deadline > elapsed {
+                return None;
+            }
+            self.queue.pop();
+            if self.deadlines.get(&promise) == Some(&deadline) {
+                self.deadlines.remove(&promise);
+                return Some(promise);
+            }
+        }
+        None
+    }
+}
+
+pub fn process_timers<C: TimerClock>(clock: Res<C>, mut commands: Commands, mut timers: ResMut<Timers<C>>) {
+    let elapsed = clock.elapsed();
+    while let Some(promise) = timers.pop_elapsed(elapsed) {
+        commands.add(PromiseCommand::resolve(promise, ()));
+    }
 }
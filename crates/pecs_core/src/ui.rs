@@ -1,6 +1,9 @@
 use bevy::prelude::*;
 
-use crate::{AsynOps, Promise, PromiseCommandsExtension, PromiseId, PromiseLikeBase};
+use crate::{
+    diagnostic::PromiseAwaits, schedule::PromiseSchedule, AsynOps, Promise, PromiseCommandsExtension, PromiseId,
+    PromiseLikeBase,
+};
 
 pub mod asyn {
     use super::AsynButton;
@@ -14,12 +17,12 @@ pub mod asyn {
 pub struct PromiseUiPlugin;
 impl Plugin for PromiseUiPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(Update, resolve_buttons);
+        PromiseSchedule::add_systems(app, resolve_buttons);
     }
 }
 
 pub struct StatefulAsynUi<S>(S);
-impl<S: 'static> StatefulAsynUi<S> {
+impl<S: 'static + Send> StatefulAsynUi<S> {
     pub fn button(self, entity: Entity) -> StatefulAsynButton<S> {
         StatefulAsynButton(self.0, entity)
     }
@@ -35,6 +38,7 @@ pub struct AsynButtonIteraction {
 pub struct AsynButton(Entity);
 
 impl AsynButton {
+    #[track_caller]
     pub fn pressed(&self) -> Promise<(), ()> {
         let entity = self.0;
         Promise::register(
@@ -57,11 +61,13 @@ impl AsynButton {
                 }
             },
         )
+        .awaits(PromiseAwaits::Button)
     }
 }
 
 pub struct StatefulAsynButton<S>(S, Entity);
-impl<S: 'static> StatefulAsynButton<S> {
+impl<S: 'static + Send> StatefulAsynButton<S> {
+    #[track_caller]
     pub fn pressed(self) -> Promise<S, ()> {
         AsynButton(self.1).pressed().with(self.0)
     }
@@ -70,22 +76,23 @@ impl<S: 'static> StatefulAsynButton<S> {
 pub trait UiOpsExtension<S> {
     fn ui(self) -> StatefulAsynUi<S>;
 }
-impl<S: 'static> UiOpsExtension<S> for AsynOps<S> {
+impl<S: 'static + Send> UiOpsExtension<S> for AsynOps<S> {
     fn ui(self) -> StatefulAsynUi<S> {
         StatefulAsynUi(self.0)
     }
 }
 
+type ChangedButtons = (Changed<Interaction>, With<Button>);
+
 fn resolve_buttons(
     mut commands: Commands,
     buttons: Query<(Entity, &AsynButtonIteraction)>,
-    interactions: Query<(Entity, &Interaction), (Changed<Interaction>, With<Button>)>,
+    interactions: Query<(Entity, &Interaction), ChangedButtons>,
 ) {
     for (btn, interaction) in interactions.iter() {
         if let Some((entity, btn)) = buttons
             .iter()
-            .filter(|(_, b)| b.entity == btn && interaction == &b.interaction)
-            .next()
+            .find(|(_, b)| b.entity == btn && interaction == &b.interaction)
         {
             commands.entity(entity).despawn();
             commands.promise(btn.promise).resolve(())
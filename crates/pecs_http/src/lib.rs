@@ -1,16 +1,15 @@
 //! Make `http` requests asyncroniusly via [`ehttp`](https://docs.rs/ehttp/)
 
 use bevy::prelude::*;
-use bevy::tasks::Task;
-use bevy::utils::HashMap;
 pub use ehttp::Response;
-use futures_lite::future;
-use pecs_core::{AsynOps, Promise, PromiseCommand, PromiseId, PromiseLikeBase, PromiseResult};
+use pecs_core::coroutine::PromiseFuture;
+use pecs_core::diagnostic::PromiseAwaits;
+use pecs_core::task::PromiseTaskPlugin;
+use pecs_core::{AsynOps, Promise, PromiseLikeBase, PromiseResult};
+use std::future::IntoFuture;
 
-#[cfg(not(target_arch = "wasm32"))]
-use bevy::tasks::AsyncComputeTaskPool;
 #[cfg(target_arch = "wasm32")]
-use pecs_core::promise_resolve;
+use pecs_core::{promise_resolve, PromiseId};
 #[cfg(target_arch = "wasm32")]
 use std::cell::Cell;
 #[cfg(target_arch = "wasm32")]
@@ -19,12 +18,9 @@ use std::rc::Rc;
 pub struct PromiseHttpPlugin;
 impl Plugin for PromiseHttpPlugin {
     fn build(&self, app: &mut App) {
-        #[cfg(not(target_arch = "wasm32"))]
-        app.init_resource::<Requests>();
-        #[cfg(not(target_arch = "wasm32"))]
-        app.add_systems(Update, process_requests);
-        // Silence unused variable warnings on non-wasm platforms
-        let _ = app;
+        if !app.is_plugin_added::<PromiseTaskPlugin>() {
+            app.add_plugins(PromiseTaskPlugin);
+        }
     }
 }
 
@@ -37,10 +33,10 @@ impl WasmResolver {
     pub fn new() -> Self {
         Self(Rc::new(Cell::new(None)))
     }
-    pub fn resolve<T: 'static>(&self, value: T) {
+    pub fn resolve<T: 'static + Send>(&self, value: T) {
         {
             let Some((id, world_ptr)) = self.0.replace(None) else {
-                return
+                return;
             };
             let world = unsafe { world_ptr.as_mut().unwrap() };
             promise_resolve(world, id, (), value);
@@ -81,6 +77,7 @@ impl Request {
         self.0.headers.insert(key.to_string(), value.to_string());
         self
     }
+    #[track_caller]
     pub fn send(self) -> Promise<(), Result<Response, String>> {
         #[cfg(target_arch = "wasm32")]
         {
@@ -97,24 +94,17 @@ impl Request {
                     discarder.discard();
                 },
             )
+            .awaits(PromiseAwaits::Http)
         }
         #[cfg(not(target_arch = "wasm32"))]
         {
-            Promise::register(
-                |world, id| {
-                    let task = AsyncComputeTaskPool::get().spawn(async move { ehttp::fetch_blocking(&self.0) });
-                    world.resource_mut::<Requests>().insert(id, task);
-                },
-                |world, id| {
-                    world.resource_mut::<Requests>().remove(&id);
-                },
-            )
+            pecs_core::task::asyn::compute(move || ehttp::fetch_blocking(&self.0)).awaits(PromiseAwaits::Http)
         }
     }
 }
 
 pub struct StatefulRequest<S>(S, Request);
-impl<S: 'static> StatefulRequest<S> {
+impl<S: 'static + Send> StatefulRequest<S> {
     pub(crate) fn new(state: S) -> Self {
         Self(state, Request::new())
     }
@@ -134,6 +124,7 @@ impl<S: 'static> StatefulRequest<S> {
         self.1 = self.1.body(body);
         self
     }
+    #[track_caller]
     pub fn send(self) -> Promise<S, Result<ehttp::Response, String>> {
         self.1.send().map(move |_| self.0)
     }
@@ -141,7 +132,7 @@ impl<S: 'static> StatefulRequest<S> {
 
 pub struct Http<S>(S);
 
-impl<S: 'static> Http<S> {
+impl<S: 'static + Send> Http<S> {
     pub fn get<U: ToString>(self, url: U) -> StatefulRequest<S> {
         StatefulRequest::new(self.0).method("GET").url(url)
     }
@@ -160,18 +151,20 @@ impl<S> HttpOpsExtension<S> for AsynOps<S> {
         Http(self.0)
     }
 }
-#[derive(Resource, Deref, DerefMut, Default)]
-pub struct Requests(HashMap<PromiseId, Task<Result<Response, String>>>);
+impl IntoFuture for Request {
+    type Output = Result<Response, String>;
+    type IntoFuture = PromiseFuture<(), Result<Response, String>>;
+    fn into_future(self) -> Self::IntoFuture {
+        self.send().into_future()
+    }
+}
 
-pub fn process_requests(mut requests: ResMut<Requests>, mut commands: Commands) {
-    requests.retain(|promise, mut task| {
-        if let Some(response) = future::block_on(future::poll_once(&mut task)) {
-            commands.add(PromiseCommand::resolve(*promise, response));
-            false
-        } else {
-            true
-        }
-    });
+impl<S: 'static + Send> IntoFuture for StatefulRequest<S> {
+    type Output = Result<Response, String>;
+    type IntoFuture = PromiseFuture<S, Result<Response, String>>;
+    fn into_future(self) -> Self::IntoFuture {
+        self.send().into_future()
+    }
 }
 
 impl From<Request> for PromiseResult<(), Result<Response, String>> {
@@ -180,7 +173,7 @@ impl From<Request> for PromiseResult<(), Result<Response, String>> {
     }
 }
 
-impl<S: 'static> From<StatefulRequest<S>> for PromiseResult<S, Result<Response, String>> {
+impl<S: 'static + Send> From<StatefulRequest<S>> for PromiseResult<S, Result<Response, String>> {
     fn from(value: StatefulRequest<S>) -> Self {
         PromiseResult::Await(value.send())
     }
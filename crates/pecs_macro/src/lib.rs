@@ -141,12 +141,11 @@ impl AsynFunc {
             "_".to_string()
         };
         let state_str = state_str.trim();
-        let mutable =
-            if false || state_str.starts_with("_") || state_str.starts_with("mut ") || state_str.starts_with("(") {
-                quote! {}
-            } else {
-                quote! { mut }
-            };
+        let mutable = if state_str.starts_with("_") || state_str.starts_with("mut ") || state_str.starts_with("(") {
+            quote! {}
+        } else {
+            quote! { mut }
+        };
 
         if self.force_loop {
             asyn_spec = quote!(::<#core::PromiseState<_>, #core::PromiseResult<_, #core::Loop<_>>, _>);
@@ -238,7 +237,7 @@ fn impl_any_promises_internal_for(elements: u8) -> TokenStream {
         let r = format_ident!("R{idx}");
         let p = format_ident!("p{idx}");
         let id = format_ident!("id{idx}");
-        in_generics = quote!(#in_generics #c #r: 'static);
+        in_generics = quote!(#in_generics #c #r: 'static + Send);
         for_args = quote!(#for_args #c Promise<(), #r>);
         type_items = quote!(#type_items #c PromiseId);
         type_result = quote!(#type_result #c Option<#r>);
@@ -276,14 +275,17 @@ fn impl_any_promises_internal_for(elements: u8) -> TokenStream {
                 .then(Asyn::<_, _, ()>::new(|In((s, r)), _| {
                     let (any_id, #promise_id_targets) = s.value.clone();
                     Promise::<(), ()>::register(
-                        move |world, _id| {
-                            #local_discards
-                            promise_resolve::<(), (#type_result)>(
-                                world,
-                                any_id,
-                                (),
-                                (#local_value),
-                            );
+                        move |world, id| {
+                            if Combined::<()>::remove(world, any_id).is_some() {
+                                #local_discards
+                                promise_resolve::<(), (#type_result)>(
+                                    world,
+                                    any_id,
+                                    (),
+                                    (#local_value),
+                                );
+                            }
+                            promise_resolve::<(), ()>(world, id, (), ());
                         },
                         |_, _| {}
                     )
@@ -301,8 +303,10 @@ fn impl_any_promises_internal_for(elements: u8) -> TokenStream {
                 let (#promise_id_targets) = (#promise_id_sources);
                 Promise::register(
                     move |world, any_id| {
+                        Combined::insert(world, any_id, ());
                         #register
-                    }, move|world, _id|{
+                    }, move|world, any_id|{
+                        Combined::<()>::remove(world, any_id);
                         #discards
                     }
                 )
@@ -338,7 +342,6 @@ fn impl_all_promises_internal_for(elements: u8) -> TokenStream {
     let mut if_all_passed = quote! {};
     let mut value_type = quote! {};
     let mut value_defaults = quote! {};
-    let mut value_clones = quote! {};
     for idx in 0..elements + 1 {
         let c = if idx == 0 { quote!() } else { quote!(,) };
         let r = format_ident!("R{idx}");
@@ -346,7 +349,7 @@ fn impl_all_promises_internal_for(elements: u8) -> TokenStream {
         let id = format_ident!("id{idx}");
         let v = format_ident!("v{idx}");
         let i = TokenStream::from_str(&format!("{idx}")).unwrap();
-        in_generics = quote!(#in_generics #c #r: 'static);
+        in_generics = quote!(#in_generics #c #r: 'static + Send);
         for_args = quote!(#for_args #c Promise<(), #r>);
         type_items = quote!(#type_items #c PromiseId);
         type_result = quote!(#type_result #c #r);
@@ -357,10 +360,6 @@ fn impl_all_promises_internal_for(elements: u8) -> TokenStream {
         value_defaults = quote!(#value_defaults #c None);
         promise_id_targets = quote!(#promise_id_targets #c #id);
         promise_id_sources = quote!(#promise_id_sources #c #p.id);
-        value_clones = quote! {
-            #value_clones
-            let #v = value.clone();
-        };
         discards = quote! {
             #discards
             promise_discard::<(), #r>(world, #id);
@@ -372,29 +371,33 @@ fn impl_all_promises_internal_for(elements: u8) -> TokenStream {
     }
     for idx in 0..elements + 1 {
         let p = format_ident!("p{idx}");
-        let v = format_ident!("v{idx}");
         let i = TokenStream::from_str(&format!("{idx}")).unwrap();
         register = quote! {
             #register
-            promise_register(world, #p.with((any_id, #v, #promise_id_targets))
+            promise_register(world, #p.with(all_id)
                 .then(Asyn::<_, _, ()>::new(|In((s, r)), _| {
-                    let (any_id, mut value, #promise_id_targets) = s.value.clone();
+                    let all_id = s.value;
                     Promise::<(), ()>::register(
-                        move |world, _id| {
-                            value.get_mut().#i = Some(r);
-                            if { value.is_valid() && { let value = value.get_ref(); true #if_all_passed }} {
-                                let (#value_names) = value.get();
+                        move |world, id| {
+                            let done = match Combined::<(#value_type)>::get_mut(world, all_id) {
+                                Some(value) => {
+                                    value.#i = Some(r);
+                                    true #if_all_passed
+                                }
+                                None => false,
+                            };
+                            if done {
+                                let (#value_names) = Combined::<(#value_type)>::remove(world, all_id).unwrap();
                                 promise_resolve::<(), (#type_result)>(
                                     world,
-                                    any_id,
+                                    all_id,
                                     (),
                                     (#value_unwraps),
                                 );
                             }
+                            promise_resolve::<(), ()>(world, id, (), ());
                         },
-                        move |world, _| {
-                            #discards
-                        }
+                        |_, _| {}
                     )
                 })),
             );
@@ -407,12 +410,12 @@ fn impl_all_promises_internal_for(elements: u8) -> TokenStream {
             fn register(self) -> Promise<(), Self::Result> {
                 let (#promise_idents) = self;
                 let (#promise_id_targets) = (#promise_id_sources);
-                let value = MutPtr::<(#value_type)>::new((#value_defaults));
-                #value_clones
                 Promise::register(
-                    move |world, any_id| {
+                    move |world, all_id| {
+                        Combined::<(#value_type)>::insert(world, all_id, (#value_defaults));
                         #register
-                    }, move|world, _id|{
+                    }, move|world, all_id|{
+                        Combined::<(#value_type)>::remove(world, all_id);
                         #discards
                     }
                 )
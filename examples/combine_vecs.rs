@@ -12,7 +12,7 @@ fn main() {
         .run();
 }
 
-const URLS: &[&'static str] = &["https://google.com", "https://bevyengine.org", "https://github.com"];
+const URLS: &[&str] = &["https://google.com", "https://bevyengine.org", "https://github.com"];
 
 fn setup(mut commands: Commands) {
     commands.add(
@@ -24,7 +24,7 @@ fn setup(mut commands: Commands) {
             // &str is the state (came from .with(url) call)
             // Result<Response, String> is the http response/error
             let requests = URLS
-                .into_iter()
+                .iter()
                 .map(|url| asyn::http::get(url).send().with(url))
                 .collect::<Vec<_>>();
 
@@ -52,7 +52,7 @@ fn setup(mut commands: Commands) {
         .then(asyn!({
             info!("Requesting any of {} urls", URLS.len());
             let requests = URLS
-                .into_iter()
+                .iter()
                 .map(|url| asyn::http::get(url).send().with(url))
                 .collect::<Vec<_>>();
             Promise::any(requests)
@@ -76,7 +76,7 @@ fn setup(mut commands: Commands) {
             // to Promise:all(), you can call .promise().all() on
             // any Iterator<Item =Promise>
             URLS
-                .into_iter()
+                .iter()
                 .map(|url| asyn::http::get(url).send().with(url))
                 .promise()
                 .all()
@@ -95,7 +95,7 @@ fn setup(mut commands: Commands) {
         .then(asyn!(_, _ => {
             info!("Requesting any of urls using iterator extension");
             URLS
-                .into_iter()
+                .iter()
                 .map(|url| asyn::http::get(url).send().with(url))
                 .promise()
                 .any()
@@ -118,7 +118,7 @@ fn setup(mut commands: Commands) {
             info!("Tracking time to get response from all requests");
             let started_at = time.elapsed_seconds();
             let requests = URLS
-                .into_iter()
+                .iter()
                 .map(|url| asyn::http::get(url).send())
                 .collect::<Vec<_>>();
             state
@@ -142,7 +142,7 @@ fn setup(mut commands: Commands) {
             // store current time to make proper calculations after resolve
             state.value = current_time;
             let requests = URLS
-                .into_iter()
+                .iter()
                 .map(|url| asyn::http::get(url).send())
                 .collect::<Vec<_>>();
             state.any(requests)
@@ -157,7 +157,7 @@ fn setup(mut commands: Commands) {
             // you need to pass context manually.
             info!("Tracking one more time the fastest one");
             URLS
-                .into_iter()
+                .iter()
                 .map(|url| asyn::http::get(url).send())
                 .promise()
                 .any()
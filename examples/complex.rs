@@ -38,20 +38,14 @@ fn setup(mut commands: Commands) {
             commands.add(|_: &mut World| info!("Executing custom command at the end."));
             let timeout = rand();
             info!("Requesting https:://google.com with timeout {timeout:0.2}s");
-            s.any((
-                // wait for first completed promise
-                asyn::timeout(timeout),
-                asyn::http::get("https://google.com").send(),
-            ))
-        }))
-        .then(asyn!(s, (timeout, response) => {
-            if timeout.is_some() {
-                info!("Request timed out");
-            } else {
-                match response.unwrap() {
-                    Ok(r) => info!("Respond faster then timeout with {}", r.status),
-                    Err(e) => info!("Respond faster then timeout with error: {e}"),
-                }
+            // the request is discarded if it doesn't respond in time
+            s.timeout(asyn::http::get("https://google.com").send(), timeout)
+        }))
+        .then(asyn!(s, response => {
+            match response {
+                Err(Elapsed) => info!("Request timed out"),
+                Ok(Ok(r)) => info!("Respond faster then timeout with {}", r.status),
+                Ok(Err(e)) => info!("Respond faster then timeout with error: {e}"),
             }
             s.pass()
         }))
@@ -99,9 +93,8 @@ fn setup(mut commands: Commands) {
             info!("Requesting all");
             ["https://google.com", "https://bevyengine.org", "https://github.com"]
                 .iter()
-                .map(|url| {
+                .inspect(|&url| {
                     info!("  {url}");
-                    url
                 })
                 .map(|url| asyn::http::get(url).send().with(*url))
                 .promise()
@@ -176,6 +169,6 @@ pub fn rand() -> f32 {
     let pid = std::process::id();
     let mut hasher = std::collections::hash_map::DefaultHasher::new();
     (epoch, pid).hash(&mut hasher);
-    let seed = hasher.finish() as u64;
+    let seed = hasher.finish();
     (seed as f32) / u64::MAX as f32
 }
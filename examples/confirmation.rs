@@ -2,7 +2,7 @@
 //! with async operations. We create `exit` button that shows
 //! confirmation popup on press and exit app if confirmed.
 //!
-//! The promise-based loop works like this:
+//! The loop is written as a coroutine with `pecs::spawn`, it works like this:
 //! - create exit button
 //! - loop:     <-------------------------.
 //!   - wait for exit button pressed      |
@@ -50,52 +50,39 @@ struct GameState {
     popup: Option<Entity>,
 }
 impl GameState {
-    /// Create the promise-based game loop
+    /// Create the game loop as a coroutine, every `.await` waits for a promise
     fn start(root: Entity) -> Promise<(), ()> {
-        Promise::from(root)
-            // Create exit button and UiState that will be passed
-            // through promises chain as `this` (just like self)
-            .then(asyn!(state, mut commands: Commands, assets: Res<AssetServer> => {
-                let root = state.value;
-                let exit = add_button("Exit", &mut commands, &assets);
-                commands.entity(root).add_child(exit);
-                state.with(GameState { root, exit, popup: None })
-            }))
-            // asyn!(this => {
-            //  loop {
-            //      asyn::ui::pressed(this.exit).await;
-            //      if this.ask_for_exit().await { break }
-            //  }
-            //  asyn::app::exit()
-            // })
+        pecs::spawn(move |_cx| async move {
+            // create exit button, system params are available inside asyn! functions
+            let exit = Promise::from(root)
+                .then(asyn!(state, mut commands: Commands, assets: Res<AssetServer> => {
+                    let exit = add_button("Exit", &mut commands, &assets);
+                    commands.entity(state.value).add_child(exit);
+                    state.resolve(exit)
+                }))
+                .await;
+            let this = GameState {
+                root,
+                exit,
+                popup: None,
+            };
             // this is the loop
-            .then_repeat(asyn!(this => {
-                let exit = this.exit; //    <------------------------------.
-                this.asyn()                                             // |
-                    // wait for exit button pressed                     // |
-                    .ui().button(exit).pressed()                        // |
-                    // show popup and wait an answer                    // |
-                    .then(asyn!(this => {                               // |
-                        info!("Exit pressed");                          // |
-                        this.ask_for_exit()                             // |
-                    }))                                                 // |
-                    .then(asyn!(this, confirmed => {                    // |
-                        info!("Exit confirmed: {confirmed}");           // |
-                        if !confirmed {                                 // |
-                            // repeat the iteration if user presses no  // |
-                            this.resolve(Repeat::Continue)  // ------------`
-                        } else {
-                            // break the loop if user presses yes
-                            this.resolve(Repeat::Break(())) // ------------.
-                        }                                               // |
-                    }))                                                 // |
-            })) //                                                      // |
-            // the next promise will be called after previous           // |
-            // `then_repeat` resolves with Repeat::Break                // |
-            .then(asyn! {   //  <------------------------------------------`
-                info!("Closing app");
-                asyn::app::exit()
-            })
+            loop {
+                // wait for exit button pressed
+                asyn::ui::button(this.exit).pressed().await;
+                info!("Exit pressed");
+                // show popup and wait an answer
+                let confirmed = this.ask_for_exit().await;
+                info!("Exit confirmed: {confirmed}");
+                if confirmed {
+                    // break the loop if user presses yes,
+                    // repeat the iteration if user presses no
+                    break;
+                }
+            }
+            info!("Closing app");
+            asyn::app::exit().await;
+        })
     }
     /// Create Promise that adds popup with text and yes/no buttons
     /// waits for one of this button got pressed and resolve with
@@ -167,7 +154,7 @@ impl GameState {
                                     TextStyle {
                                         font: asset_server.load("fonts/FiraSans-Bold.ttf"),
                                         font_size: 40.0,
-                                        color: COLOR_DARK.into(),
+                                        color: COLOR_DARK,
                                     },
                                 ));
                                 let mut buttons = layout.spawn(NodeBundle {
@@ -0,0 +1,30 @@
+//! This example does the same job as `simple`, but the
+//! chain is written with `async`/`await` via `pecs::spawn`.
+use bevy::prelude::*;
+use pecs::prelude::*;
+fn main() {
+    App::new()
+        .add_plugins(DefaultPlugins)
+        .add_plugins(PecsPlugin)
+        .add_systems(Startup, setup)
+        .run();
+}
+
+fn setup(mut commands: Commands) {
+    commands.add(pecs::spawn(|cx| async move {
+        // system params are available via cx.run()
+        let start = cx.run(asyn!(time: Res<Time> => time.elapsed_seconds())).await;
+        info!("Wait a second..");
+        // any promise can be awaited
+        cx.timeout(1.0).await;
+        info!("How large is is the Bevy main web page?");
+        match cx.http().get("https://bevyengine.org").await {
+            Ok(response) => info!("It is {} bytes!", response.bytes.len()),
+            Err(err) => info!("Ahhh... something goes wrong: {err}"),
+        }
+        let duration = cx.run(asyn!(time: Res<Time> => time.elapsed_seconds())).await - start;
+        info!("It tooks {duration:0.2}s to do this job.");
+        info!("Exiting now");
+        asyn::app::exit().await;
+    }));
+}
@@ -25,9 +25,61 @@
 //! - Result mapping via [`with_result(value)`][core::PromiseLikeBase::with_result]/
 //!   [`map_result(func)`][core::PromiseLikeBase::map_result] (changes result type over
 //!   chain calls).
+//! - Error channel for `Result` promises via [`ok_then()`][core::PromiseLikeResult::ok_then]/
+//!   [`err_then()`][core::PromiseLikeResult::err_then]/[`catch()`][core::PromiseLikeResult::catch]
+//!   (errors skip the rest of the chain and land in one handler).
+//! - Deadlines via [`timeout(secs)`][core::PromiseLikeTimeout::timeout]/
+//!   [`timeout_or(secs, default)`][core::PromiseLikeTimeout::timeout_or] for stateless promises
+//!   and [`state.timeout(promise, secs)`][core::PromiseState::timeout] in stateful chains.
+//! - Timers on virtual, real or custom clocks via [`PromiseTimer`][core::timer::PromiseTimer],
+//!   per-chain time scale via [`time_scale(scale)`][core::Promise::time_scale] and repeating ticks via
+//!   [`asyn::timer::interval(period)`][core::timer::interval].
+//! - Retrying fallible promises with fixed or exponential backoff via
+//!   [`Promise::retry(policy, func)`][core::Promise::retry].
+//! - Mapping collections with bounded concurrency via
+//!   [`Promise::map_concurrent(items, limit, func)`][core::Promise::map_concurrent]/
+//!   [`Promise::try_map_concurrent(items, limit, func)`][core::Promise::try_map_concurrent].
+//! - Multi-value [`PromiseStream`][core::stream::PromiseStream]s fed by
+//!   [`StreamSender`][core::stream::StreamSender] with `next`/`for_each`/`fold`/`map`/`filter`/`take`/`until`.
+//! - Configurable schedule via [`PecsPlugin::in_schedule(schedule)`][prelude::PecsPlugin::in_schedule] and
+//!   per-chain [`in_schedule(schedule)`][core::Promise::in_schedule] to run the rest of the chain in e.g. `FixedUpdate`.
+//! - Frame-accurate waits via [`asyn::next_frame()`][core::frame::next_frame]/[`asyn::frames(n)`][core::frame::frames]/
+//!   [`asyn::next_fixed_update()`][core::frame::next_fixed_update]/[`asyn::end_of_frame()`][core::frame::end_of_frame].
+//! - Waiting for the next Bevy event via [`asyn::event::next::<E>()`][core::event::asyn::next]/
+//!   [`asyn::event::next_matching(predicate)`][core::event::asyn::next_matching].
+//! - Watching components of the entity via [`asyn::component::added/changed/removed(entity)`][core::component::asyn]
+//!   and [`asyn::component::until(entity, predicate)`][core::component::asyn::until].
+//! - Waiting for the world to match the condition via [`asyn::until(asyn!(params => bool))`][core::condition::until]/
+//!   [`asyn::until_some(asyn!(params => Option<T>))`][core::condition::until_some].
+//! - Bounded and unbounded channels between systems and chains via [`pecs::channel()`][core::channel::channel]/
+//!   [`pecs::bounded(capacity)`][core::channel::bounded] with [`recv()`][core::channel::Receiver::recv] promises.
+//! - Opt-in panic isolation for `asyn!` functions via
+//!   [`PecsPlugin::panic_policy(policy)`][prelude::PecsPlugin::panic_policy] or
+//!   [`PromisePanicPolicy`][core::panic::PromisePanicPolicy] resource.
+//! - Cancelling queued chains via [`PromiseHandle`][core::PromiseHandle] returned by
+//!   [`promise.handle()`][core::Promise::handle]/[`chain.handle()`][core::PromiseChain::handle].
+//! - Entity-owned chains via [`commands.entity(e).promise(|e| state)`][core::PromiseEntityCommandsExtension::promise]
+//!   or [`owned_by(entity)`][core::Promise::owned_by] discarded when the owner is despawned.
+//! - State-scoped chains via [`in_state(state)`][core::Promise::in_state] and transition promises
+//!   via [`asyn::state::set(next)`][core::state::asyn::set] (requires
+//!   [`PromiseStatePlugin`][core::state::PromiseStatePlugin]).
+//! - Background work on Bevy task pools via [`asyn::task::spawn(future)`][core::task::asyn::spawn]/
+//!   [`asyn::task::compute(func)`][core::task::asyn::compute]/[`asyn::task::io(future)`][core::task::asyn::io]
+//!   (the task is cancelled when the chain is discarded).
+//! - `async`/`await` authoring via [`pecs::spawn(|cx| async move { .. })`][core::coroutine::spawn]: any
+//!   promise can be awaited inside and [`cx.run(asyn!(..))`][core::coroutine::Cx::run] gives access
+//!   to system params.
+//! - Opt-in introspection of the waiting promises via [`PendingPromises`][core::diagnostic::PendingPromises]
+//!   resource (labels are set with [`named(label)`][core::Promise::named]) and in-game overlay via
+//!   [`PendingPromisesOverlayPlugin`][core::diagnostic::PendingPromisesOverlayPlugin].
+//! - Opt-in warnings about stalled, dropped and unknown promises via
+//!   [`PromiseDebug`][core::diagnostic::PromiseDebug] resource.
+//! - `tracing` spans for chains (`promise` span keyed by id and label) and their steps
+//!   (`then`/`start` spans with the creation site), visible in Tracy or chrome traces.
+//! - Headless [`PromiseTestApp`][testing::PromiseTestApp] with virtual time for testing chains.
 //!
 //! ## Example
-//! ```rust
+//! ```rust,no_run
 //! use bevy::prelude::*;
 //! use pecs::prelude::*;
 //! fn main() {
@@ -107,7 +159,8 @@
 //!
 //! The resulting promise has the signature [`Promise<S, R>`][prelude::Promise], where `R`
 //! is the type of the result and `S` is the type of the promise state. Note that `R` and `S`
-//! must be `'static` types, so references or lifetime types are not allowed.
+//! must be `'static + Send` types, so references, lifetime types or `Rc` are not allowed: promises
+//! are passed through [`Commands`][bevy::prelude::Commands].
 //!
 //! Promises can be chained together using the [`then()`][core::PromiseLikeBase::then] method, which
 //! takes an [`Asyn`][struct@core::Asyn] function created with the [`asyn!`][prelude::asyn!] macro. The
@@ -256,30 +309,83 @@
 //! There are a lot docs planned to put here, but I believe it is better to release `something`
 //! then `perfect`.
 
+// lets `asyn!` expand to `pecs::` paths inside this crate
+extern crate self as pecs;
+
 /// All you need is `use pecs::prelude::*`
 pub mod prelude {
     // structs
     #[doc(inline)]
+    pub use pecs_core::component::Despawned;
+    #[doc(inline)]
+    pub use pecs_core::coroutine::Cx;
+    #[doc(inline)]
+    pub use pecs_core::diagnostic::PendingPromises;
+    #[doc(inline)]
+    pub use pecs_core::diagnostic::PendingPromisesOverlayPlugin;
+    #[doc(inline)]
+    pub use pecs_core::diagnostic::PromiseDebug;
+    #[doc(inline)]
+    pub use pecs_core::panic::PromisePanicPolicy;
+    #[doc(inline)]
+    pub use pecs_core::retry::Retried;
+    #[doc(inline)]
+    pub use pecs_core::retry::RetryPolicy;
+    #[doc(inline)]
+    pub use pecs_core::schedule::PromiseSet;
+    #[doc(inline)]
+    pub use pecs_core::state::PromiseStatePlugin;
+    #[doc(inline)]
+    pub use pecs_core::stream::PromiseStream;
+    #[doc(inline)]
+    pub use pecs_core::stream::StreamSender;
+    #[doc(inline)]
+    pub use pecs_core::timer::Elapsed;
+    #[doc(inline)]
+    pub use pecs_core::timer::PromiseTimer;
+    #[doc(inline)]
     pub use pecs_core::Promise;
     #[doc(inline)]
     pub use pecs_core::PromiseCommand;
     #[doc(inline)]
+    pub use pecs_core::PromiseHandle;
+    #[doc(inline)]
     pub use pecs_core::PromiseId;
     #[doc(inline)]
     pub use pecs_core::Repeat;
 
     // traits
     #[doc(inline)]
+    pub use pecs_core::component::ComponentOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::condition::ConditionOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::event::EventOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::frame::FrameOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::state::StateOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::task::TaskOpsExtension;
+    #[doc(inline)]
+    pub use pecs_core::timer::TimerClock;
+    #[doc(inline)]
     pub use pecs_core::timer::TimerOpsExtension;
     #[doc(inline)]
     pub use pecs_core::ui::UiOpsExtension;
     #[doc(inline)]
     pub use pecs_core::PromiseCommandsExtension;
     #[doc(inline)]
+    pub use pecs_core::PromiseEntityCommandsExtension;
+    #[doc(inline)]
     pub use pecs_core::PromiseLike;
     #[doc(inline)]
     pub use pecs_core::PromiseLikeBase;
     #[doc(inline)]
+    pub use pecs_core::PromiseLikeResult;
+    #[doc(inline)]
+    pub use pecs_core::PromiseLikeTimeout;
+    #[doc(inline)]
     pub use pecs_core::PromisesExtension;
     #[doc(inline)]
     pub use pecs_http::HttpOpsExtension;
@@ -290,16 +396,121 @@ pub mod prelude {
     #[doc(inline)]
     pub use pecs_macro::asyn;
 
+    use bevy::ecs::schedule::{InternedScheduleLabel, InternedSystemSet, ScheduleLabel};
     use bevy::prelude::*;
+    use pecs_core::schedule::PromiseSchedule;
+
+    /// Adds promise processing to the app. Promises are resolved in [`Update`] by default,
+    /// [`PecsPlugin::in_schedule()`] moves processing to another schedule and
+    /// [`PecsPlugin::panic_policy()`] sets up panic isolation.
     pub struct PecsPlugin;
+    impl PecsPlugin {
+        /// Creates the plugin resolving promises in the `schedule`:
+        /// ```ignore
+        /// App::new()
+        ///     .add_plugins(DefaultPlugins)
+        ///     .add_plugins(PecsPlugin::in_schedule(PostUpdate).in_set(MyGameplaySet))
+        ///     .run();
+        /// ```
+        /// Promise systems are grouped in [`PromiseSet`], which can be ordered with
+        /// [`App::configure_sets()`] as any other set.
+        pub fn in_schedule(schedule: impl ScheduleLabel) -> ScheduledPecsPlugin {
+            ScheduledPecsPlugin {
+                schedule: schedule.intern(),
+                sets: vec![],
+                panic_policy: None,
+            }
+        }
+        /// Creates the plugin handling panics of the promise callbacks with the `policy`:
+        /// ```ignore
+        /// App::new()
+        ///     .add_plugins(DefaultPlugins)
+        ///     .add_plugins(PecsPlugin::panic_policy(PromisePanicPolicy::LogAndDiscard))
+        ///     .run();
+        /// ```
+        /// The policy is stored in the [`PromisePanicPolicy`] resource, so it can be changed later.
+        pub fn panic_policy(policy: PromisePanicPolicy) -> ScheduledPecsPlugin {
+            PecsPlugin::in_schedule(Update).panic_policy(policy)
+        }
+    }
     impl Plugin for PecsPlugin {
         fn build(&self, app: &mut App) {
+            PecsPlugin::in_schedule(Update).build(app);
+        }
+        fn finish(&self, app: &mut App) {
+            PecsPlugin::in_schedule(Update).finish(app);
+        }
+    }
+
+    /// [`PecsPlugin`] configured with [`PecsPlugin::in_schedule()`] or [`PecsPlugin::panic_policy()`]
+    pub struct ScheduledPecsPlugin {
+        schedule: InternedScheduleLabel,
+        sets: Vec<InternedSystemSet>,
+        panic_policy: Option<PromisePanicPolicy>,
+    }
+    impl ScheduledPecsPlugin {
+        /// Puts [`PromiseSet`] into the `set`, so promises are resolved with the `set` systems
+        pub fn in_set(mut self, set: impl SystemSet) -> Self {
+            self.sets.push(set.intern());
+            self
+        }
+        /// Handles panics of the promise callbacks with the `policy`, see [`PecsPlugin::panic_policy()`]
+        pub fn panic_policy(mut self, policy: PromisePanicPolicy) -> Self {
+            self.panic_policy = Some(policy);
+            self
+        }
+    }
+    impl Plugin for ScheduledPecsPlugin {
+        fn build(&self, app: &mut App) {
+            app.insert_resource(PromiseSchedule(self.schedule));
+            for set in self.sets.iter() {
+                app.configure_sets(self.schedule, PromiseSet.in_set(*set));
+            }
+            app.register_type::<pecs_core::PromiseId>();
             app.init_resource::<pecs_core::timer::Timers>();
-            app.add_systems(Update, pecs_core::timer::process_timers);
+            PromiseSchedule::add_systems(app, pecs_core::timer::process_timers::<Time<Virtual>>);
+            app.init_resource::<pecs_core::PromiseCancellations>();
+            app.init_resource::<pecs_core::PromiseOwners>();
+            app.init_resource::<pecs_core::PromiseResolvers>();
+            PromiseSchedule::add_systems(app, pecs_core::process_promise_resolvers);
+            match self.panic_policy {
+                Some(policy) => app.insert_resource(policy),
+                None => app.init_resource::<PromisePanicPolicy>(),
+            };
+            PromiseSchedule::add_systems(
+                app,
+                (
+                    pecs_core::diagnostic::detect_stalled_promises,
+                    pecs_core::diagnostic::warn_dropped_promises,
+                ),
+            );
+            PromiseSchedule::add_systems(
+                app,
+                (
+                    pecs_core::discard_orphaned_promises,
+                    pecs_core::process_cancelled_promises,
+                )
+                    .chain(),
+            );
 
+            app.add_plugins(pecs_core::schedule::PromiseSchedulePlugin);
+            app.add_plugins(pecs_core::task::PromiseTaskPlugin);
+            app.add_plugins(pecs_core::coroutine::PromiseCoroutinePlugin);
+            app.add_plugins(pecs_core::channel::PromiseChannelPlugin);
+            app.add_plugins(pecs_core::condition::PromiseConditionPlugin);
+            app.add_plugins(pecs_core::frame::PromiseFramePlugin);
             app.add_plugins(pecs_http::PromiseHttpPlugin);
             app.add_plugins(pecs_core::ui::PromiseUiPlugin);
         }
+        fn finish(&self, app: &mut App) {
+            // promises are tracked only when the debug warnings need them
+            if let Some(debug) = app.world.get_resource::<pecs_core::diagnostic::PromiseDebug>() {
+                if debug.warn_dropped {
+                    app.init_resource::<pecs_core::diagnostic::DroppedPromises>();
+                }
+                app.init_resource::<pecs_core::diagnostic::PendingPromises>();
+            }
+        }
     }
 
     /// Out-of-the box async operations
@@ -307,6 +518,20 @@ pub mod prelude {
         #[doc(inline)]
         pub use pecs_core::app;
         #[doc(inline)]
+        pub use pecs_core::component::asyn as component;
+        #[doc(inline)]
+        pub use pecs_core::condition::{until, until_some};
+        #[doc(inline)]
+        pub use pecs_core::event::asyn as event;
+        #[doc(inline)]
+        pub use pecs_core::frame::{end_of_frame, frames, next_fixed_update, next_frame};
+        #[doc(inline)]
+        pub use pecs_core::state::asyn as state;
+        #[doc(inline)]
+        pub use pecs_core::task::asyn as task;
+        #[doc(inline)]
+        pub use pecs_core::timer::asyn as timer;
+        #[doc(inline)]
         pub use pecs_core::timer::timeout;
         #[doc(inline)]
         pub use pecs_core::ui::asyn as ui;
@@ -315,9 +540,14 @@ pub mod prelude {
     }
 }
 
+pub mod testing;
+
 #[doc(inline)]
 pub use pecs_core as core;
 #[doc(inline)]
+pub use pecs_core::channel::{bounded, channel};
+pub use pecs_core::coroutine::spawn;
+#[doc(inline)]
 pub use pecs_core::timer;
 #[doc(inline)]
 pub use pecs_http as http;
@@ -0,0 +1,148 @@
+//! Headless app with the virtual time for testing promise chains
+//! ```rust
+//! use bevy::prelude::*;
+//! use pecs::prelude::*;
+//! use pecs::testing::PromiseTestApp;
+//! use std::time::Duration;
+//!
+//! let mut app = PromiseTestApp::new();
+//! let promise = app.run(asyn::timeout(1.0).then(asyn!(_ => {
+//!     Promise::resolve("done")
+//! })));
+//! app.advance(Duration::from_secs_f32(0.5));
+//! app.assert_pending(&promise);
+//! app.advance(Duration::from_secs_f32(0.5));
+//! app.assert_resolved(&promise, "done");
+//! ```
+use crate::prelude::*;
+use bevy::{ecs::system::Command, prelude::*, time::TimeUpdateStrategy};
+use std::{
+    fmt::Debug,
+    ops::{Deref, DerefMut},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// Headless [`App`] with [`MinimalPlugins`] and [`PecsPlugin`] which time
+/// advances only via [`advance()`][PromiseTestApp::advance]. Dereferences to the
+/// [`App`], so systems, resources and states can be added as usual.
+pub struct PromiseTestApp {
+    app: App,
+    started: bool,
+}
+
+impl Default for PromiseTestApp {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PromiseTestApp {
+    pub fn new() -> PromiseTestApp {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins).add_plugins(PecsPlugin);
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+        app.world
+            .resource_mut::<Time<Virtual>>()
+            .set_max_delta(Duration::from_secs(u32::MAX as u64));
+        PromiseTestApp { app, started: false }
+    }
+
+    /// Registers the `promise` and returns [`TestPromise`] that tracks its result.
+    /// Synchronous steps of the chain run immediately.
+    pub fn run<S: 'static + Send, R: 'static + Send>(&mut self, promise: Promise<S, R>) -> TestPromise<S, R> {
+        self.start();
+        let slot = Arc::new(Mutex::new(None));
+        let result = slot.clone();
+        let mut promise = promise.map(move |state| (state, result)).then(asyn!(s, r => {
+            let (state, result) = s.value;
+            *result.lock().unwrap() = Some((state, r));
+        }));
+        let handle = promise.handle();
+        promise.apply(&mut self.app.world);
+        TestPromise { handle, slot }
+    }
+
+    /// Runs single frame advancing the time by `duration`
+    pub fn advance(&mut self, duration: Duration) {
+        self.start();
+        self.app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(duration))
+            .update();
+        self.app
+            .insert_resource(TimeUpdateStrategy::ManualDuration(Duration::ZERO));
+    }
+
+    /// Runs `frames` frames without advancing the time
+    pub fn step_frames(&mut self, frames: usize) {
+        self.start();
+        for _ in 0..frames {
+            self.app.update();
+        }
+    }
+
+    /// Panics if the `promise` is not resolved with `value`
+    #[track_caller]
+    pub fn assert_resolved<S, R: PartialEq + Debug>(&self, promise: &TestPromise<S, R>, value: R) {
+        match &*promise.slot.lock().unwrap() {
+            Some((_, result)) => assert_eq!(result, &value, "{:?} resolved with unexpected value", promise.handle),
+            None => panic!("{:?} is not resolved", promise.handle),
+        }
+    }
+
+    /// Panics if the `promise` is not pending
+    #[track_caller]
+    pub fn assert_pending<S, R>(&self, promise: &TestPromise<S, R>) {
+        assert!(promise.handle.is_pending(), "{:?} is not pending", promise.handle);
+    }
+
+    /// Panics if the `promise` is not discarded
+    #[track_caller]
+    pub fn assert_discarded<S, R>(&self, promise: &TestPromise<S, R>) {
+        assert!(
+            !promise.handle.is_pending() && !promise.handle.is_resolved(),
+            "{:?} is not discarded",
+            promise.handle
+        );
+    }
+
+    /// Finishes the plugins and runs the startup schedules with zero delta time once
+    fn start(&mut self) {
+        if !self.started {
+            self.started = true;
+            self.app.finish();
+            self.app.cleanup();
+            self.app.update();
+        }
+    }
+}
+
+impl Deref for PromiseTestApp {
+    type Target = App;
+    fn deref(&self) -> &Self::Target {
+        &self.app
+    }
+}
+
+impl DerefMut for PromiseTestApp {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.app
+    }
+}
+
+/// Promise registered with [`PromiseTestApp::run()`]
+pub struct TestPromise<S, R> {
+    handle: PromiseHandle,
+    slot: Arc<Mutex<Option<(S, R)>>>,
+}
+
+impl<S, R> TestPromise<S, R> {
+    /// Handle of the registered chain, can be used to cancel it
+    pub fn handle(&self) -> &PromiseHandle {
+        &self.handle
+    }
+    /// Takes the state and the result of the resolved promise
+    pub fn take(&self) -> Option<(S, R)> {
+        self.slot.lock().unwrap().take()
+    }
+}
@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use pecs::core::timer::Timers;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+use std::{sync::Arc, time::Duration};
+
+fn secs(secs: f64) -> Duration {
+    Duration::from_secs_f64(secs)
+}
+
+#[test]
+fn then_runs_after_timeout() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(
+        asyn::timeout(1.0)
+            .then(asyn!(_ => asyn::timeout(0.5)))
+            .then(asyn!(_, _, time: Res<Time> => Promise::resolve(time.elapsed_seconds()))),
+    );
+    app.advance(secs(0.9));
+    app.assert_pending(&promise);
+    app.advance(secs(0.1));
+    app.assert_pending(&promise);
+    app.advance(secs(0.5));
+    app.assert_resolved(&promise, 1.5);
+}
+
+#[test]
+fn sync_chain_resolves_immediately() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(Promise::start(asyn!(_ => Promise::resolve(2))).then(asyn!(_, n => Promise::resolve(n * 3))));
+    app.assert_resolved(&promise, 6);
+}
+
+#[test]
+fn repeat_counts_timeouts() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(Promise::repeat(
+        0,
+        asyn!(s => {
+            s.value += 1;
+            let count = s.value;
+            s.asyn().timeout(1.0).with_result(if count < 3 {
+                Repeat::Continue
+            } else {
+                Repeat::Break(count)
+            })
+        }),
+    ));
+    app.advance(secs(1.0));
+    app.advance(secs(1.0));
+    app.assert_pending(&promise);
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, 3);
+    assert_eq!(promise.take().map(|(state, _)| state), Some(3));
+}
+
+#[test]
+fn any_resolves_with_first() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(Promise::any(vec![
+        asyn::timeout(2.0).with("slow"),
+        asyn::timeout(1.0).with("fast"),
+    ]));
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, ("fast", ()));
+}
+
+#[test]
+fn all_waits_for_every_promise() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(Promise::all(vec![
+        asyn::timeout(2.0).with("slow"),
+        asyn::timeout(1.0).with("fast"),
+    ]));
+    app.advance(secs(1.0));
+    app.assert_pending(&promise);
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, vec![("slow", ()), ("fast", ())]);
+}
+
+#[test]
+fn cancel_discards_chain() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<PendingPromises>();
+    let promise = app.run(asyn::timeout(1.0));
+    promise.handle().cancel();
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+    app.advance(secs(1.0));
+    app.assert_discarded(&promise);
+    assert!(app.world.resource::<PendingPromises>().is_empty());
+}
+
+#[test]
+fn all_tuple_resolves_in_order() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(Promise::all((
+        asyn::timeout(2.0).with_result("slow"),
+        asyn::timeout(1.0).with_result(1),
+    )));
+    app.advance(secs(1.0));
+    app.assert_pending(&promise);
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, ("slow", 1));
+}
+
+#[test]
+fn any_tuple_resolves_with_first() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(Promise::any((
+        asyn::timeout(2.0).with_result("slow"),
+        asyn::timeout(1.0).with_result(1),
+    )));
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, (None, Some(1)));
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, (None, Some(1)));
+}
+
+#[test]
+fn discarded_all_frees_partial_results() {
+    let mut app = PromiseTestApp::new();
+    let tracker = Arc::new(());
+    let promise = app.run(Promise::all(vec![
+        asyn::timeout(1.0).with(tracker.clone()),
+        asyn::timeout(2.0).with(tracker.clone()),
+    ]));
+    app.advance(secs(1.0));
+    app.assert_pending(&promise);
+    promise.handle().cancel();
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+    assert_eq!(Arc::strong_count(&tracker), 1);
+}
+
+#[test]
+fn timeout_passes_result_in_time() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(asyn::timeout(1.0).with_result(5).timeout(2.0));
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, Ok(5));
+}
+
+#[test]
+fn timeout_discards_slow_promise() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(asyn::timeout(3.0).with_result(5).timeout(1.0));
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, Err(Elapsed));
+    assert!(app.world.resource::<Timers>().is_empty());
+    let fallback = app.run(asyn::timeout(3.0).with_result(5).timeout_or(1.0, 0));
+    app.advance(secs(1.0));
+    app.assert_resolved(&fallback, 0);
+}
+
+#[test]
+fn timeout_keeps_state() {
+    let mut app = PromiseTestApp::new();
+    let timed = |deadline: f64| {
+        Promise::from("state")
+            .map(move |state| (state, deadline))
+            .then(asyn!(s => {
+                let (state, deadline) = s.value;
+                s.with(state).timeout(asyn::timeout(2.0).with_result(5), deadline)
+            }))
+    };
+    let in_time = app.run(timed(3.0));
+    let late = app.run(timed(1.0));
+    app.advance(secs(1.0));
+    assert_eq!(late.take(), Some(("state", Err(Elapsed))));
+    app.advance(secs(1.0));
+    assert_eq!(in_time.take(), Some(("state", Ok(5))));
+}
+
+#[test]
+fn timeout_or_keeps_state() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(Promise::from(7).then(asyn!(s => {
+        s.timeout_or(asyn::timeout(2.0).with_result(5), 1.0, 0)
+    })));
+    app.advance(secs(1.0));
+    assert_eq!(promise.take(), Some((7, 0)));
+    assert!(app.world.resource::<Timers>().is_empty());
+}
+
+fn fallible(ok: bool) -> Promise<u32, Result<u32, String>> {
+    Promise::from(2).map_result(move |_| if ok { Ok(3) } else { Err("failed".to_string()) })
+}
+
+#[test]
+fn ok_then_runs_on_ok() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(fallible(true).ok_then(asyn!(s, value => {
+        let total = (s.value * value) as u64;
+        s.map(|count| count as u64 + 1).resolve(Ok::<u64, String>(total))
+    })));
+    assert_eq!(promise.take(), Some((3u64, Ok(6))));
+}
+
+#[test]
+fn ok_then_skips_err() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(
+        fallible(false)
+            .ok_then(asyn!(s, value => s.map(u64::from).resolve(Ok::<u64, String>(value as u64))))
+            .err_then(asyn!(s, err => s.with(err.len() as u64).resolve(Err::<u64, usize>(0)))),
+    );
+    assert_eq!(promise.take(), Some((6, Err(0))));
+
+    let skipped = app.run(fallible(true).err_then(asyn!(s, _err => {
+        s.map(u64::from).resolve(Ok::<u32, ()>(0))
+    })));
+    assert_eq!(skipped.take(), Some((2u64, Ok(3))));
+}
+
+#[test]
+fn catch_recovers_from_err() {
+    let mut app = PromiseTestApp::new();
+    let recovered = app.run(fallible(false).catch(asyn!(s, err => {
+        s.with(err.len() as u64).resolve(0)
+    })));
+    assert_eq!(recovered.take(), Some((6, 0)));
+
+    let passed = app.run(
+        fallible(true)
+            .catch(asyn!(s, _err => s.map(u64::from).resolve(0)))
+            .then(asyn!(s, value => s.resolve(value * 10))),
+    );
+    assert_eq!(passed.take(), Some((2u64, 30)));
+}
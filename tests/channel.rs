@@ -0,0 +1,118 @@
+use pecs::core::channel::{Channels, TrySendError};
+use pecs::testing::PromiseTestApp;
+
+#[test]
+fn recv_in_send_order() {
+    let mut app = PromiseTestApp::new();
+    let (sender, receiver) = pecs::channel::<u32>();
+    let first = app.run(receiver.recv());
+    let second = app.run(receiver.recv());
+    app.step_frames(1);
+    app.assert_pending(&first);
+    sender.try_send(1).unwrap();
+    sender.clone().try_send(2).unwrap();
+    app.step_frames(1);
+    app.assert_resolved(&first, Some(1));
+    app.assert_resolved(&second, Some(2));
+    assert!(app.world.resource::<Channels>().is_empty());
+}
+
+#[test]
+fn recv_in_registration_order() {
+    let mut app = PromiseTestApp::new();
+    let (sender, receiver) = pecs::channel::<u32>();
+    // created first, registered last
+    let late = receiver.recv();
+    let first = app.run(receiver.recv());
+    let second = app.run(late);
+    sender.try_send(1).unwrap();
+    app.step_frames(1);
+    app.assert_resolved(&first, Some(1));
+    app.assert_pending(&second);
+    sender.try_send(2).unwrap();
+    app.step_frames(1);
+    app.assert_resolved(&second, Some(2));
+}
+
+#[test]
+fn sent_values_resolve_right_away() {
+    let mut app = PromiseTestApp::new();
+    let (sender, receiver) = pecs::channel();
+    sender.try_send("hello").unwrap();
+    let promise = app.run(receiver.recv());
+    assert_eq!(promise.take(), Some(((), Some("hello"))));
+}
+
+#[test]
+fn bounded_send_waits_for_room() {
+    let mut app = PromiseTestApp::new();
+    let (sender, receiver) = pecs::bounded(1);
+    sender.try_send(1).unwrap();
+    assert_eq!(sender.try_send(2), Err(TrySendError::Full(2)));
+    let send = app.run(sender.send(2));
+    app.step_frames(2);
+    app.assert_pending(&send);
+    assert_eq!(receiver.try_recv(), Some(1));
+    app.step_frames(1);
+    app.assert_resolved(&send, Ok(()));
+    assert_eq!(receiver.try_recv(), Some(2));
+}
+
+#[test]
+fn closed_channel_ends_recv() {
+    let mut app = PromiseTestApp::new();
+    let (sender, receiver) = pecs::channel();
+    sender.try_send(1).unwrap();
+    sender.close();
+    assert_eq!(sender.try_send(2), Err(TrySendError::Closed(2)));
+    let first = app.run(receiver.recv());
+    let second = app.run(receiver.recv());
+    app.step_frames(1);
+    app.assert_resolved(&first, Some(1));
+    app.assert_resolved(&second, None);
+    assert!(receiver.is_closed());
+}
+
+#[test]
+fn dropped_senders_end_recv() {
+    let mut app = PromiseTestApp::new();
+    let (sender, receiver) = pecs::channel::<u32>();
+    let promise = app.run(receiver.recv());
+    let other = sender.clone();
+    drop(sender);
+    app.step_frames(2);
+    app.assert_pending(&promise);
+    drop(other);
+    app.step_frames(2);
+    app.assert_resolved(&promise, None);
+}
+
+#[test]
+fn dropped_receiver_fails_send() {
+    let mut app = PromiseTestApp::new();
+    let (sender, receiver) = pecs::bounded(1);
+    sender.try_send(1).unwrap();
+    let send = app.run(sender.send(2));
+    app.step_frames(1);
+    app.assert_pending(&send);
+    drop(receiver);
+    app.step_frames(2);
+    app.assert_resolved(&send, Err(2));
+    assert!(sender.is_closed());
+}
+
+#[test]
+fn discarded_recv_is_removed() {
+    let mut app = PromiseTestApp::new();
+    let (sender, receiver) = pecs::channel();
+    let discarded = app.run(receiver.recv());
+    app.step_frames(1);
+    discarded.handle().cancel();
+    app.step_frames(1);
+    app.assert_discarded(&discarded);
+    assert!(app.world.resource::<Channels>().is_empty());
+    // the discarded promise doesn't block the next ones
+    sender.try_send(5).unwrap();
+    let promise = app.run(receiver.recv());
+    assert_eq!(promise.take(), Some(((), Some(5))));
+}
@@ -0,0 +1,85 @@
+use bevy::prelude::*;
+use pecs::core::component::ComponentWaiters;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Health(u32);
+
+#[test]
+fn added_ignores_existing_component() {
+    let mut app = PromiseTestApp::new();
+    let entity = app.world.spawn(Health(10)).id();
+    let promise = app.run(asyn::component::added::<Health>(entity));
+    app.step_frames(2);
+    app.assert_pending(&promise);
+    app.world.entity_mut(entity).remove::<Health>();
+    app.step_frames(1);
+    app.assert_pending(&promise);
+    app.world.entity_mut(entity).insert(Health(5));
+    app.step_frames(1);
+    app.assert_resolved(&promise, Ok(Health(5)));
+    assert!(app.world.resource::<ComponentWaiters<Health>>().is_empty());
+}
+
+#[test]
+fn changed_resolves_with_component() {
+    let mut app = PromiseTestApp::new();
+    let entity = app.world.spawn(Health(10)).id();
+    let promise = app.run(asyn::component::changed::<Health>(entity));
+    app.step_frames(2);
+    app.assert_pending(&promise);
+    app.world.get_mut::<Health>(entity).unwrap().0 = 7;
+    app.step_frames(1);
+    app.assert_resolved(&promise, Ok(Health(7)));
+}
+
+#[test]
+fn removed_resolves_without_component() {
+    let mut app = PromiseTestApp::new();
+    let entity = app.world.spawn(Health(10)).id();
+    let promise = app.run(asyn::component::removed::<Health>(entity));
+    app.step_frames(2);
+    app.assert_pending(&promise);
+    app.world.entity_mut(entity).remove::<Health>();
+    app.step_frames(1);
+    app.assert_resolved(&promise, Ok(()));
+}
+
+#[test]
+fn until_checks_predicate() {
+    let mut app = PromiseTestApp::new();
+    let entity = app.world.spawn(Health(10)).id();
+    let promise = app.run(asyn::component::until(entity, |h: &Health| h.0 == 0));
+    app.world.get_mut::<Health>(entity).unwrap().0 = 3;
+    app.step_frames(1);
+    app.assert_pending(&promise);
+    app.world.get_mut::<Health>(entity).unwrap().0 = 0;
+    app.step_frames(1);
+    app.assert_resolved(&promise, Ok(Health(0)));
+}
+
+#[test]
+fn despawn_resolves_with_error() {
+    let mut app = PromiseTestApp::new();
+    let entity = app.world.spawn(Health(10)).id();
+    let changed = app.run(asyn::component::changed::<Health>(entity));
+    let removed = app.run(asyn::component::removed::<Health>(entity));
+    app.step_frames(1);
+    app.world.despawn(entity);
+    app.step_frames(1);
+    app.assert_resolved(&changed, Err(Despawned(entity)));
+    app.assert_resolved(&removed, Err(Despawned(entity)));
+}
+
+#[test]
+fn discarded_waiter_is_removed() {
+    let mut app = PromiseTestApp::new();
+    let entity = app.world.spawn(Health(10)).id();
+    let promise = app.run(asyn::component::changed::<Health>(entity));
+    app.step_frames(1);
+    promise.handle().cancel();
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+    assert!(app.world.resource::<ComponentWaiters<Health>>().is_empty());
+}
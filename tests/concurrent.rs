@@ -0,0 +1,67 @@
+use pecs::core::timer::Timers;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+use std::time::Duration;
+
+fn secs(secs: f32) -> Duration {
+    Duration::from_secs_f32(secs)
+}
+
+#[test]
+fn keeps_limit_and_input_order() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(Promise::map_concurrent(
+        [3.0, 1.0, 2.0, 1.0],
+        2,
+        asyn!(s => asyn::timeout(s.value).with_result(s.value as u32)),
+    ));
+    assert_eq!(app.world.resource::<Timers>().len(), 2);
+    app.advance(secs(1.0));
+    app.assert_pending(&promise);
+    assert_eq!(app.world.resource::<Timers>().len(), 2);
+    app.advance(secs(3.0));
+    app.assert_pending(&promise);
+    assert_eq!(app.world.resource::<Timers>().len(), 1);
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, vec![3, 1, 2, 1]);
+}
+
+#[test]
+fn sync_items_resolve_immediately() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(Promise::map_concurrent(
+        0..10_000,
+        4,
+        asyn!(s => Promise::resolve(s.value * 2)),
+    ));
+    app.assert_resolved(&promise, (0..10_000).map(|i| i * 2).collect());
+}
+
+#[test]
+fn try_map_fails_fast() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(Promise::try_map_concurrent(
+        [1.0, 2.0, 5.0, 1.0],
+        3,
+        asyn!(s => {
+            let secs = s.value;
+            asyn::timeout(secs).with_result(if secs > 1.5 { Err(secs) } else { Ok(secs) })
+        }),
+    ));
+    app.advance(secs(1.0));
+    app.assert_pending(&promise);
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, Err(2.0));
+    assert!(app.world.resource::<Timers>().is_empty());
+}
+
+#[test]
+fn try_map_collects_oks() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(Promise::try_map_concurrent(
+        vec!["a", "b"],
+        1,
+        asyn!(s => Promise::resolve(Ok::<_, ()>(s.value.to_uppercase()))),
+    ));
+    app.assert_resolved(&promise, Ok(vec!["A".to_string(), "B".to_string()]));
+}
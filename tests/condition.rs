@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+use pecs::core::condition::Conditions;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+
+#[derive(Component, Clone, Debug, PartialEq)]
+struct Health(u32);
+
+#[derive(Resource, Default)]
+struct Score(u32);
+
+#[test]
+fn until_waits_for_condition() {
+    let mut app = PromiseTestApp::new();
+    let boss = app.world.spawn(Health(10)).id();
+    let promise = app.run(asyn::until(asyn!(q: Query<&Health> => q.iter().all(|h| h.0 == 0))));
+    app.step_frames(2);
+    app.assert_pending(&promise);
+    app.world.get_mut::<Health>(boss).unwrap().0 = 0;
+    app.step_frames(1);
+    app.assert_resolved(&promise, ());
+    assert!(app.world.resource::<Conditions>().is_empty());
+}
+
+#[test]
+fn matching_condition_resolves_right_away() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<Score>();
+    let promise = app.run(asyn::until(asyn!(score: Res<Score> => score.0 == 0)));
+    app.assert_resolved(&promise, ());
+}
+
+#[test]
+fn until_some_resolves_with_value() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<Score>();
+    let promise = app.run(asyn::until_some(
+        asyn!(score: Res<Score> => (score.0 >= 3).then_some(score.0)),
+    ));
+    app.world.resource_mut::<Score>().0 = 2;
+    app.step_frames(1);
+    app.assert_pending(&promise);
+    app.world.resource_mut::<Score>().0 = 5;
+    app.step_frames(1);
+    app.assert_resolved(&promise, 5);
+}
+
+#[test]
+fn change_filters_keep_state() {
+    let mut app = PromiseTestApp::new();
+    app.world.spawn(Health(10));
+    // the first check sees the component as changed, the next one doesn't
+    let promise = app.run(asyn::until(asyn!(q: Query<&Health, Changed<Health>> => q.is_empty())));
+    app.assert_pending(&promise);
+    app.step_frames(1);
+    app.assert_resolved(&promise, ());
+}
+
+#[test]
+fn discarded_condition_is_removed() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<Score>();
+    let promise = app.run(asyn::until(asyn!(score: Res<Score> => score.0 > 0)));
+    app.step_frames(1);
+    promise.handle().cancel();
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+    assert!(app.world.resource::<Conditions>().is_empty());
+}
@@ -0,0 +1,94 @@
+use bevy::prelude::*;
+use pecs::core::timer::Timers;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+use std::time::Duration;
+
+fn millis(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[derive(Resource, Default)]
+struct Score(u32);
+
+#[test]
+fn spawn_resolves_with_output() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(pecs::spawn(|_cx| async move { "done" }));
+    app.step_frames(1);
+    app.assert_resolved(&promise, "done");
+}
+
+#[test]
+fn spawn_awaits_promises() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<Score>();
+    let promise = app.run(pecs::spawn(|cx| async move {
+        let first = asyn::timeout(0.5).map_result(|_| 1).await;
+        cx.timeout(0.5).await;
+        let score = cx.run(asyn!(mut score: ResMut<Score> => {
+            score.0 += 10;
+            score.0
+        }));
+        first + score.await
+    }));
+    // the first poll registers the first timeout, every await takes one frame
+    app.step_frames(1);
+    app.advance(millis(500));
+    app.assert_pending(&promise);
+    app.advance(millis(500));
+    app.assert_resolved(&promise, 11);
+    assert_eq!(app.world.resource::<Score>().0, 10);
+}
+
+#[test]
+fn each_await_takes_one_frame() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(pecs::spawn(|cx| async move {
+        for _ in 0..3 {
+            cx.timeout(1.0).await;
+        }
+        "done"
+    }));
+    app.step_frames(1);
+    app.advance(millis(1000));
+    app.advance(millis(1000));
+    app.assert_pending(&promise);
+    app.advance(millis(1000));
+    app.assert_resolved(&promise, "done");
+}
+
+#[test]
+fn discarded_mid_await() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(pecs::spawn(|cx| async move {
+        cx.timeout(1.0).await;
+        cx.run(asyn!(mut commands: Commands => commands.init_resource::<Score>()))
+            .await;
+    }));
+    app.step_frames(1);
+    assert_eq!(app.world.resource::<Timers>().len(), 1);
+    promise.handle().cancel();
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+    assert!(app.world.resource::<Timers>().is_empty());
+    app.advance(millis(1000));
+    assert!(!app.world.contains_resource::<Score>());
+}
+
+#[test]
+fn discarded_awaited_promise_discards_coroutine() {
+    let mut app = PromiseTestApp::new();
+    let mut timeout = asyn::timeout(1.0);
+    let handle = timeout.handle();
+    let promise = app.run(pecs::spawn(move |_cx| async move {
+        timeout.await;
+        "done"
+    }));
+    app.step_frames(1);
+    handle.cancel();
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+    app.advance(millis(1000));
+    app.assert_discarded(&promise);
+}
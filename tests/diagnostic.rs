@@ -0,0 +1,146 @@
+use bevy::{ecs::system::Command, prelude::*};
+use pecs::core::diagnostic::{DroppedPromises, PendingPromises, PromiseAwaits, PromiseDebug};
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+use std::time::Duration;
+
+fn millis(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+fn timers(app: &PromiseTestApp) -> Vec<(Option<String>, bool)> {
+    app.world
+        .resource::<PendingPromises>()
+        .iter()
+        .filter(|promise| promise.awaits == PromiseAwaits::Timer)
+        .map(|promise| (promise.label.clone(), promise.is_stalled()))
+        .collect()
+}
+
+#[test]
+fn named_label_passes_to_nested_promises() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<PendingPromises>();
+    let promise = app.run(
+        asyn::timeout(1.)
+            .then(asyn!(_ => asyn::timeout(1.)))
+            .named("load level"),
+    );
+    app.step_frames(1);
+    assert_eq!(timers(&app), vec![(Some("load level".to_string()), false)]);
+    app.advance(millis(1000));
+    assert_eq!(timers(&app), vec![(Some("load level".to_string()), false)]);
+    app.advance(millis(1000));
+    app.assert_resolved(&promise, ());
+    assert!(app.world.resource::<PendingPromises>().is_empty());
+}
+
+#[test]
+fn stalled_promises_are_detected_after_threshold() {
+    let mut app = PromiseTestApp::new();
+    app.insert_resource(PromiseDebug {
+        stall_threshold: 1.,
+        ..Default::default()
+    });
+    let promise = app.run(asyn::timeout(5.));
+    app.advance(millis(500));
+    assert_eq!(timers(&app), vec![(None, false)]);
+    app.advance(millis(600));
+    assert_eq!(timers(&app), vec![(None, true)]);
+    // composite promises only wait for the nested ones and are never reported
+    let pending = app.world.resource::<PendingPromises>();
+    assert!(pending
+        .iter()
+        .filter(|promise| promise.awaits == PromiseAwaits::Chain)
+        .all(|promise| !promise.is_stalled()));
+    app.advance(millis(4000));
+    app.assert_resolved(&promise, ());
+}
+
+#[test]
+fn stalled_promises_are_not_detected_without_debug() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<PendingPromises>();
+    app.run(asyn::timeout(30.));
+    app.advance(millis(20000));
+    assert_eq!(timers(&app), vec![(None, false)]);
+}
+
+#[test]
+fn pending_promises_are_opt_in() {
+    let mut app = PromiseTestApp::new();
+    app.run(asyn::timeout(1.));
+    assert!(!app.world.contains_resource::<PendingPromises>());
+
+    let mut app = PromiseTestApp::new();
+    app.insert_resource(PromiseDebug::default());
+    app.run(asyn::timeout(1.));
+    assert_eq!(timers(&app), vec![(None, false)]);
+}
+
+fn debug_app(debug: PromiseDebug) -> PromiseTestApp {
+    let mut app = PromiseTestApp::new();
+    app.insert_resource(debug);
+    app
+}
+
+fn dropped(app: &PromiseTestApp) -> Option<usize> {
+    app.world.get_resource::<DroppedPromises>().map(DroppedPromises::len)
+}
+
+/// Chain dropping the promise it creates instead of returning it
+fn dropping_chain() -> Promise<(), ()> {
+    Promise::start(asyn!(_ => {
+        drop(asyn::timeout(1.0));
+    }))
+}
+
+#[test]
+fn dropped_promise_reported_in_its_world() {
+    let mut first = debug_app(PromiseDebug::default());
+    let mut second = debug_app(PromiseDebug::default());
+    second.run(asyn::timeout(1.0));
+    let promise = first.run(dropping_chain());
+    first.assert_resolved(&promise, ());
+    assert_eq!(dropped(&first), Some(1));
+    assert_eq!(dropped(&second), Some(0));
+    first.step_frames(1);
+    assert_eq!(dropped(&first), Some(0));
+}
+
+#[test]
+fn dropped_promise_not_recorded_without_warn_dropped() {
+    let mut app = debug_app(PromiseDebug {
+        warn_dropped: false,
+        ..default()
+    });
+    app.run(dropping_chain());
+    assert_eq!(dropped(&app), None);
+
+    app.world.resource_mut::<PromiseDebug>().warn_dropped = true;
+    app.step_frames(1);
+    app.run(dropping_chain());
+    assert_eq!(dropped(&app), Some(1));
+
+    app.world.resource_mut::<PromiseDebug>().warn_dropped = false;
+    app.step_frames(1);
+    assert_eq!(dropped(&app), None);
+}
+
+#[derive(Resource)]
+struct Waiting(PromiseId);
+
+#[test]
+fn resolving_unknown_promise_is_ignored() {
+    let mut app = debug_app(PromiseDebug::default());
+    let promise = app.run(Promise::<(), u32>::register(
+        |world, id| world.insert_resource(Waiting(id)),
+        |_, _| {},
+    ));
+    PromiseCommand::resolve(PromiseId::new(), 1u32).apply(&mut app.world);
+    let id = app.world.resource::<Waiting>().0;
+    PromiseCommand::resolve(id, 2u32).apply(&mut app.world);
+    PromiseCommand::resolve(id, 3u32).apply(&mut app.world);
+    app.step_frames(1);
+    app.assert_resolved(&promise, 2);
+}
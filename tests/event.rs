@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+use pecs::core::event::EventWaiters;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+
+#[derive(Event, Clone, Debug, PartialEq)]
+struct Damage(u32);
+
+#[derive(Event, Clone, Debug, PartialEq)]
+struct Heal(u32);
+
+fn app() -> PromiseTestApp {
+    let mut app = PromiseTestApp::new();
+    app.add_event::<Damage>();
+    app.add_event::<Heal>();
+    app
+}
+
+#[test]
+fn next_skips_events_sent_before() {
+    let mut app = app();
+    app.world.send_event(Damage(1));
+    let promise = app.run(asyn::event::next::<Damage>());
+    app.step_frames(2);
+    app.assert_pending(&promise);
+    app.world.send_event(Damage(2));
+    app.world.send_event(Damage(3));
+    app.step_frames(1);
+    app.assert_resolved(&promise, Damage(2));
+    assert!(app.world.resource::<EventWaiters<Damage>>().is_empty());
+}
+
+#[test]
+fn every_waiter_reads_events() {
+    let mut app = app();
+    let first = app.run(asyn::event::next::<Damage>());
+    let second = app.run(asyn::event::next::<Damage>());
+    let heal = app.run(asyn::event::next::<Heal>());
+    app.world.send_event(Damage(5));
+    app.step_frames(1);
+    app.assert_resolved(&first, Damage(5));
+    app.assert_resolved(&second, Damage(5));
+    app.assert_pending(&heal);
+    app.world.send_event(Heal(1));
+    app.step_frames(1);
+    app.assert_resolved(&heal, Heal(1));
+}
+
+#[test]
+fn next_matching_filters_events() {
+    let mut app = app();
+    let promise = app.run(asyn::event::next_matching(|d: &Damage| d.0 > 10));
+    app.world.send_event(Damage(3));
+    app.step_frames(1);
+    app.assert_pending(&promise);
+    app.world.send_event(Damage(7));
+    app.world.send_event(Damage(12));
+    app.step_frames(1);
+    app.assert_resolved(&promise, Damage(12));
+}
+
+#[test]
+fn chained_waiters() {
+    let mut app = app();
+    let promise = app.run(
+        asyn::event::next::<Damage>()
+            .then(asyn!(_, damage => asyn::event::next::<Heal>().map(move |_| damage)))
+            .then(asyn!(s, heal => {
+                let damage = s.value.0;
+                s.resolve((damage, heal.0))
+            })),
+    );
+    app.world.send_event(Damage(4));
+    app.world.send_event(Heal(1));
+    app.step_frames(1);
+    app.assert_pending(&promise);
+    app.world.send_event(Heal(2));
+    app.step_frames(1);
+    app.assert_resolved(&promise, (4, 2));
+}
+
+#[test]
+fn discarded_waiter_is_removed() {
+    let mut app = app();
+    let promise = app.run(asyn::event::next::<Damage>());
+    app.step_frames(1);
+    promise.handle().cancel();
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+    assert!(app.world.resource::<EventWaiters<Damage>>().is_empty());
+}
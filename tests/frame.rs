@@ -0,0 +1,64 @@
+use pecs::core::frame::Frames;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+use std::time::Duration;
+
+#[test]
+fn frames_count_updates() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(asyn::frames(3));
+    app.step_frames(2);
+    app.assert_pending(&promise);
+    app.step_frames(1);
+    app.assert_resolved(&promise, ());
+    assert!(app.world.resource::<Frames>().is_empty());
+
+    let promise = app.run(asyn::frames(0));
+    app.assert_resolved(&promise, ());
+}
+
+#[test]
+fn next_frame_ignores_time() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(asyn::next_frame());
+    app.advance(Duration::from_secs(10));
+    app.assert_resolved(&promise, ());
+}
+
+#[test]
+fn end_of_frame_follows_next_frame() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(asyn::next_frame().then(asyn!(_ => asyn::end_of_frame())));
+    app.step_frames(1);
+    app.assert_resolved(&promise, ());
+
+    // registered in `Last` after the waiters are resolved
+    let promise = app.run(asyn::end_of_frame().then(asyn!(_ => asyn::end_of_frame())));
+    app.step_frames(1);
+    app.assert_pending(&promise);
+    app.step_frames(1);
+    app.assert_resolved(&promise, ());
+}
+
+#[test]
+fn next_fixed_update_waits_for_fixed_step() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(asyn::next_fixed_update());
+    app.step_frames(3);
+    app.assert_pending(&promise);
+    app.advance(Duration::from_millis(20));
+    app.assert_resolved(&promise, ());
+}
+
+#[test]
+fn discarded_wait_is_removed() {
+    let mut app = PromiseTestApp::new();
+    let frames = app.run(asyn::frames(5));
+    let fixed = app.run(asyn::next_fixed_update());
+    frames.handle().cancel();
+    fixed.handle().cancel();
+    app.step_frames(1);
+    app.assert_discarded(&frames);
+    app.assert_discarded(&fixed);
+    assert!(app.world.resource::<Frames>().is_empty());
+}
@@ -0,0 +1,79 @@
+use bevy::prelude::*;
+use pecs::core::timer::Timers;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+use std::time::Duration;
+
+fn secs(secs: f64) -> Duration {
+    Duration::from_secs_f64(secs)
+}
+
+#[derive(Resource, Default)]
+struct Logged(u32);
+
+#[test]
+fn despawned_owner_discards_chain() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<Logged>();
+    let owner = app.world.spawn_empty().id();
+    let promise = app.run(
+        asyn::timeout(1.0)
+            .then(asyn!(_, mut logged: ResMut<Logged> => logged.0 += 1))
+            .then(asyn!(_ => asyn::timeout(1.0)))
+            .owned_by(owner),
+    );
+    app.advance(secs(0.5));
+    app.assert_pending(&promise);
+    assert_eq!(app.world.resource::<Timers>().len(), 1);
+    app.world.despawn(owner);
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+    assert!(app.world.resource::<Timers>().is_empty());
+    app.advance(secs(2.0));
+    assert_eq!(app.world.resource::<Logged>().0, 0);
+}
+
+#[test]
+fn despawned_owner_discards_nested_pending_chain() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<Logged>();
+    let owner = app.world.spawn_empty().id();
+    let promise = app.run(
+        asyn::timeout(1.0)
+            .then(asyn!(_ => asyn::timeout(1.0)))
+            .then(asyn!(_, mut logged: ResMut<Logged> => logged.0 += 1))
+            .owned_by(owner),
+    );
+    app.advance(secs(1.5));
+    app.assert_pending(&promise);
+    assert_eq!(app.world.resource::<Timers>().len(), 1);
+    app.world.despawn(owner);
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+    assert!(app.world.resource::<Timers>().is_empty());
+    app.advance(secs(1.0));
+    assert_eq!(app.world.resource::<Logged>().0, 0);
+}
+
+#[test]
+fn despawned_ancestor_discards_chain() {
+    let mut app = PromiseTestApp::new();
+    let parent = app.world.spawn_empty().id();
+    let owner = app.world.spawn_empty().set_parent(parent).id();
+    let promise = app.run(asyn::timeout(1.0).owned_by(owner));
+    app.step_frames(1);
+    app.world.entity_mut(parent).despawn_recursive();
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+    assert!(app.world.resource::<Timers>().is_empty());
+}
+
+#[test]
+fn alive_owner_keeps_chain() {
+    let mut app = PromiseTestApp::new();
+    let owner = app.world.spawn_empty().id();
+    let promise = app.run(asyn::timeout(1.0).then(asyn!(_ => asyn::timeout(1.0))).owned_by(owner));
+    app.advance(secs(1.0));
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, ());
+}
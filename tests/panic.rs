@@ -0,0 +1,113 @@
+use bevy::{ecs::system::Command, prelude::*};
+use pecs::core::panic::PromisePanic;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+
+fn app(policy: PromisePanicPolicy) -> PromiseTestApp {
+    let mut app = PromiseTestApp::new();
+    app.insert_resource(policy);
+    app
+}
+
+#[test]
+#[should_panic(expected = "boom")]
+fn abort_tears_down() {
+    let mut app = PromiseTestApp::new();
+    app.run(asyn::timeout(0.).then(asyn!(_ => {
+        if true {
+            panic!("boom");
+        }
+    })));
+    app.step_frames(1);
+}
+
+#[test]
+fn log_and_discard() {
+    let mut app = app(PromisePanicPolicy::LogAndDiscard);
+    app.init_resource::<PendingPromises>();
+    let panicked = app.run(asyn::timeout(0.).then(asyn!(_ => {
+        if true {
+            panic!("boom");
+        }
+    })));
+    let other = app.run(asyn::timeout(0.).with_result("done"));
+    app.step_frames(1);
+    app.assert_discarded(&panicked);
+    app.assert_resolved(&other, "done");
+    assert!(app.world.resource::<PendingPromises>().is_empty());
+}
+
+#[test]
+fn convert_to_error() {
+    let mut app = app(PromisePanicPolicy::ConvertToError);
+    let promise = app.run(
+        asyn::timeout(0.)
+            .with_result(Ok::<u32, PromisePanic>(1))
+            .ok_then(asyn!(s, value => {
+                if value > 0 {
+                    panic!("boom");
+                }
+                s.resolve(Ok::<u32, PromisePanic>(value))
+            })),
+    );
+    let message = app.run(
+        asyn::timeout(0.)
+            .with_result(Err::<u32, String>("failed".into()))
+            .err_then(asyn!(s, err => {
+                if !err.is_empty() {
+                    panic!("boom");
+                }
+                s.resolve(Ok::<u32, String>(0))
+            })),
+    );
+    app.step_frames(1);
+    let Some(((), Err(panic))) = promise.take() else {
+        panic!("promise is not resolved with error");
+    };
+    assert_eq!(panic.message, "boom");
+    let Some(((), Err(message))) = message.take() else {
+        panic!("promise is not resolved with error");
+    };
+    assert!(message.ends_with("boom"), "{message}");
+}
+
+#[test]
+fn convert_to_error_discards_stateful_steps() {
+    let mut app = app(PromisePanicPolicy::ConvertToError);
+    let promise = app.run(
+        Promise::from(5)
+            .map_result(|_| Ok::<u32, String>(1))
+            .ok_then(asyn!(s, value => {
+                if value > 0 {
+                    panic!("boom");
+                }
+                s.resolve(Ok::<u32, String>(value))
+            })),
+    );
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+}
+
+#[test]
+fn plugin_sets_panic_policy() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(PecsPlugin::panic_policy(PromisePanicPolicy::LogAndDiscard));
+    assert_eq!(
+        *app.world.resource::<PromisePanicPolicy>(),
+        PromisePanicPolicy::LogAndDiscard
+    );
+    let mut promise = Promise::start(asyn!(_ => {
+        if true {
+            panic!("boom");
+        }
+    }));
+    let handle = promise.handle();
+    promise.apply(&mut app.world);
+    app.update();
+    assert!(!handle.is_pending() && !handle.is_resolved());
+
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins).add_plugins(PecsPlugin);
+    assert_eq!(*app.world.resource::<PromisePanicPolicy>(), PromisePanicPolicy::Abort);
+}
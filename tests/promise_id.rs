@@ -0,0 +1,25 @@
+use pecs::prelude::*;
+use std::{collections::HashSet, thread};
+
+#[test]
+fn ids_are_unique_across_threads() {
+    let workers: Vec<_> = (0..4)
+        .map(|_| thread::spawn(|| (0..1000).map(|_| PromiseId::new()).collect::<Vec<_>>()))
+        .collect();
+    let mut ids: HashSet<_> = (0..1000).map(|_| PromiseId::new()).collect();
+    for worker in workers {
+        for id in worker.join().unwrap() {
+            assert!(ids.insert(id), "{id} allocated twice");
+        }
+    }
+}
+
+#[test]
+fn id_roundtrips_through_bits_and_serde() {
+    let id = PromiseId::new();
+    assert_eq!(PromiseId::from_bits(id.to_bits()), id);
+    let json = serde_json::to_string(&id).unwrap();
+    assert_eq!(json, id.to_bits().to_string());
+    assert_eq!(serde_json::from_str::<PromiseId>(&json).unwrap(), id);
+    assert_eq!(format!("{id}"), format!("Promise({})", id.to_bits()));
+}
@@ -0,0 +1,81 @@
+use pecs::core::PromiseState;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+use std::time::Duration;
+
+fn secs(secs: f32) -> Duration {
+    Duration::from_secs_f32(secs)
+}
+
+/// Fails twice, succeeds with the third attempt
+fn flaky(policy: RetryPolicy<&'static str>) -> Promise<u32, Retried<u32, &'static str>> {
+    PromiseState::new(0).retry(
+        policy,
+        asyn!(s => {
+            s.value += 1;
+            let result = if s.value >= 3 { Ok(s.value) } else { Err("flaky") };
+            s.resolve(result)
+        }),
+    )
+}
+
+#[test]
+fn retries_with_exponential_backoff() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(flaky(RetryPolicy::new(5).exponential(1.0, 2.0)));
+    // the first attempt fails immediately and waits 1s, the second one waits 2s
+    app.assert_pending(&promise);
+    app.advance(secs(1.0));
+    app.assert_pending(&promise);
+    app.advance(secs(0.5));
+    app.assert_pending(&promise);
+    app.advance(secs(1.5));
+    app.assert_resolved(
+        &promise,
+        Retried {
+            result: Ok(3),
+            attempts: 3,
+        },
+    );
+}
+
+#[test]
+fn gives_up_after_max_attempts() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(flaky(RetryPolicy::new(2).fixed(0.5)));
+    app.advance(secs(0.5));
+    app.assert_resolved(
+        &promise,
+        Retried {
+            result: Err("flaky"),
+            attempts: 2,
+        },
+    );
+}
+
+#[test]
+fn skips_non_retryable_errors() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(flaky(RetryPolicy::new(5).retry_if(|err| *err != "flaky")));
+    app.assert_resolved(
+        &promise,
+        Retried {
+            result: Err("flaky"),
+            attempts: 1,
+        },
+    );
+}
+
+#[test]
+fn backoff_delays() {
+    let policy = RetryPolicy::<()>::new(5).exponential(1.0, 2.0).max_delay(3.0);
+    assert_eq!(
+        (1..5).map(|n| policy.delay(n)).collect::<Vec<_>>(),
+        vec![1.0, 2.0, 3.0, 3.0]
+    );
+    let policy = RetryPolicy::<()>::new(5).fixed(1.0).jitter(0.5);
+    for attempt in 1..100 {
+        let delay = policy.delay(attempt);
+        assert!((0.5..=1.5).contains(&delay), "{delay} is out of jitter range");
+    }
+}
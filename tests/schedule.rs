@@ -0,0 +1,82 @@
+use bevy::ecs::system::Command;
+use bevy::prelude::*;
+use pecs::core::schedule::PromiseSchedule;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+use std::time::Duration;
+
+/// The schedule currently running
+#[derive(Resource, Default)]
+struct Stage(&'static str);
+
+fn app() -> PromiseTestApp {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<Stage>();
+    app.add_systems(PreUpdate, |mut stage: ResMut<Stage>| stage.0 = "pre_update");
+    app.add_systems(Update, |mut stage: ResMut<Stage>| stage.0 = "update");
+    app.add_systems(PostUpdate, |mut stage: ResMut<Stage>| stage.0 = "post_update");
+    app
+}
+
+fn stage() -> Asyn![() => (), &'static str] {
+    asyn!(_, stage: Res<Stage> => Promise::resolve(stage.0))
+}
+
+#[test]
+fn chain_runs_in_schedule() {
+    let mut app = app();
+    let pre = app.run(Promise::from(()).in_schedule(PreUpdate).then(stage()));
+    let post = app.run(Promise::from(()).in_schedule(PostUpdate).then(stage()));
+    app.assert_pending(&pre);
+    app.assert_pending(&post);
+    app.step_frames(1);
+    app.assert_resolved(&pre, "pre_update");
+    app.assert_resolved(&post, "post_update");
+}
+
+#[test]
+fn fixed_update_waits_for_fixed_step() {
+    let mut app = app();
+    let promise = app.run(asyn::timeout(0.).in_schedule(FixedUpdate));
+    app.step_frames(2);
+    app.assert_pending(&promise);
+    app.advance(Duration::from_millis(20));
+    app.assert_resolved(&promise, ());
+}
+
+#[test]
+fn discarded_before_flush() {
+    let mut app = app();
+    let promise = app.run(Promise::from(()).in_schedule(PostUpdate).then(stage()));
+    promise.handle().cancel();
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+}
+
+#[test]
+fn plugin_in_schedule() {
+    let mut app = App::new();
+    app.add_plugins(MinimalPlugins)
+        .add_plugins(PecsPlugin::in_schedule(PostUpdate));
+    assert_eq!(
+        format!("{:?}", app.world.resource::<PromiseSchedule>().0),
+        format!("{:?}", PostUpdate)
+    );
+    app.init_resource::<Stage>();
+    app.add_systems(Update, |mut stage: ResMut<Stage>| stage.0 = "update");
+    app.add_systems(
+        PostUpdate,
+        (|mut stage: ResMut<Stage>| stage.0 = "post_update").before(PromiseSet),
+    );
+    asyn::timeout(0.)
+        .then(asyn!(_, stage: Res<Stage>, mut commands: Commands => {
+            commands.insert_resource(Resolved(stage.0));
+        }))
+        .apply(&mut app.world);
+    app.update();
+    app.update();
+    assert_eq!(app.world.resource::<Resolved>().0, "post_update");
+}
+
+#[derive(Resource)]
+struct Resolved(&'static str);
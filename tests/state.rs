@@ -0,0 +1,95 @@
+use bevy::prelude::*;
+use pecs::core::state::PromiseStatePlugin;
+use pecs::core::timer::Timers;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+use std::time::Duration;
+
+fn secs(secs: f64) -> Duration {
+    Duration::from_secs_f64(secs)
+}
+
+#[derive(States, Clone, Copy, PartialEq, Eq, Hash, Debug, Default)]
+enum GameState {
+    #[default]
+    Menu,
+    Playing,
+}
+
+#[derive(Resource, Default)]
+struct Entered(u32);
+
+fn state_app() -> PromiseTestApp {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<Entered>();
+    app.add_state::<GameState>();
+    app.add_plugins(PromiseStatePlugin::<GameState>::default());
+    app.add_systems(OnEnter(GameState::Playing), |mut entered: ResMut<Entered>| {
+        entered.0 += 1
+    });
+    app
+}
+
+fn go(app: &mut PromiseTestApp, state: GameState) {
+    app.world.resource_mut::<NextState<GameState>>().set(state);
+    app.step_frames(1);
+}
+
+#[test]
+fn leaving_state_discards_chain() {
+    let mut app = state_app();
+    go(&mut app, GameState::Playing);
+    let promise = app.run(
+        asyn::timeout(1.0)
+            .then(asyn!(_, mut entered: ResMut<Entered> => entered.0 += 10))
+            .in_state(GameState::Playing),
+    );
+    app.advance(secs(0.5));
+    app.assert_pending(&promise);
+    go(&mut app, GameState::Menu);
+    app.assert_discarded(&promise);
+    assert!(app.world.resource::<Timers>().is_empty());
+    app.advance(secs(1.0));
+    assert_eq!(app.world.resource::<Entered>().0, 1);
+}
+
+#[test]
+fn staying_in_state_keeps_chain() {
+    let mut app = state_app();
+    go(&mut app, GameState::Playing);
+    let promise = app.run(asyn::timeout(1.0).in_state(GameState::Playing));
+    go(&mut app, GameState::Playing);
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, ());
+}
+
+#[test]
+fn chain_outside_state_discarded_immediately() {
+    let mut app = state_app();
+    let promise = app.run(asyn::timeout(1.0).in_state(GameState::Playing));
+    app.assert_discarded(&promise);
+    assert!(app.world.resource::<Timers>().is_empty());
+}
+
+#[test]
+fn set_resolves_after_transition() {
+    let mut app = state_app();
+    let promise = app.run(asyn::state::set(GameState::Playing).then(asyn!(
+        _,
+        state: Res<State<GameState>>,
+        entered: Res<Entered> => Promise::resolve((*state.get(), entered.0))
+    )));
+    app.assert_pending(&promise);
+    app.step_frames(1);
+    app.assert_resolved(&promise, (GameState::Playing, 1));
+}
+
+#[test]
+fn set_current_state_resolves_without_transition() {
+    let mut app = state_app();
+    go(&mut app, GameState::Playing);
+    let promise = app.run(asyn::state::set(GameState::Playing));
+    app.step_frames(1);
+    app.assert_resolved(&promise, ());
+    assert_eq!(app.world.resource::<Entered>().0, 1);
+}
@@ -0,0 +1,120 @@
+use pecs::core::channel::Channels;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+use std::time::Duration;
+
+fn secs(secs: f32) -> Duration {
+    Duration::from_secs_f32(secs)
+}
+
+#[test]
+fn for_each_awaits_values() {
+    let mut app = PromiseTestApp::new();
+    let (sender, stream) = PromiseStream::channel();
+    let promise = app.run(stream.with(vec![]).for_each(asyn!(s, value => {
+        s.value.push(value);
+        s.asyn().timeout(1.0)
+    })));
+    sender.send(1);
+    sender.send(2);
+    app.step_frames(1);
+    app.assert_pending(&promise);
+    app.advance(secs(1.0));
+    app.advance(secs(1.0));
+    drop(sender);
+    app.step_frames(1);
+    assert_eq!(promise.take().map(|(values, _)| values), Some(vec![1, 2]));
+}
+
+#[test]
+fn combinators() {
+    let mut app = PromiseTestApp::new();
+    let (sender, stream) = PromiseStream::channel();
+    let promise = app.run(stream.filter(|v| v % 2 == 0).map(|v| v * 10).take(3).collect());
+    for value in 0..4 {
+        assert!(sender.send(value));
+    }
+    app.step_frames(1);
+    app.assert_pending(&promise);
+    for value in 4..10 {
+        sender.send(value);
+    }
+    app.step_frames(1);
+    app.assert_resolved(&promise, vec![0, 20, 40]);
+    // take() drops the source after the last value
+    assert!(!sender.send(10));
+}
+
+#[test]
+fn next_returns_stream() {
+    let mut app = PromiseTestApp::new();
+    let (sender, stream) = PromiseStream::channel();
+    sender.send("first");
+    sender.send("second");
+    let promise = app.run(
+        stream
+            .next()
+            .then(asyn!(s, first => s.value.next().map_result(move |second| (first, second)).map(|_| ()))),
+    );
+    app.assert_resolved(&promise, (Some("first"), Some("second")));
+}
+
+#[test]
+fn fold_until_timeout() {
+    let mut app = PromiseTestApp::new();
+    let (sender, stream) = PromiseStream::channel();
+    let promise = app.run(stream.until(asyn::timeout(1.0)).fold(0, |sum, v| sum + v));
+    sender.send(1);
+    sender.send(2);
+    app.step_frames(1);
+    app.assert_pending(&promise);
+    app.advance(secs(1.0));
+    app.assert_resolved(&promise, 3);
+    assert!(!sender.send(3));
+    assert!(app.world.resource::<Channels>().is_empty());
+}
+
+#[test]
+fn discard_closes_stream() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<PendingPromises>();
+    let (sender, stream) = PromiseStream::<(), u32>::channel();
+    let promise = app.run(stream.until(asyn::timeout(10.0)).collect());
+    app.step_frames(1);
+    assert!(!app.world.resource::<Channels>().is_empty());
+    promise.handle().cancel();
+    app.step_frames(2);
+    app.assert_discarded(&promise);
+    assert!(sender.is_closed());
+    assert!(app.world.resource::<Channels>().is_empty());
+    assert!(app.world.resource::<PendingPromises>().is_empty());
+}
+
+#[test]
+fn long_buffered_stream_folds() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<PendingPromises>();
+    let (sender, stream) = PromiseStream::channel();
+    for value in 0..10_000u64 {
+        sender.send(value);
+    }
+    drop(sender);
+    let promise = app.run(stream.filter(|v| v % 2 == 0).fold(0, |sum, v| sum + v));
+    app.step_frames(1);
+    app.assert_resolved(&promise, (0..10_000).step_by(2).sum());
+    assert!(app.world.resource::<PendingPromises>().is_empty());
+}
+
+#[test]
+fn endless_stream_keeps_pending_flat() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<PendingPromises>();
+    let promise = app.run(asyn::timer::interval(0.01).for_each(asyn!(_, _ => {})));
+    app.advance(secs(0.01));
+    let pending = app.world.resource::<PendingPromises>().len();
+    for _ in 0..1000 {
+        app.advance(secs(0.01));
+    }
+    app.assert_pending(&promise);
+    assert_eq!(app.world.resource::<PendingPromises>().len(), pending);
+}
@@ -0,0 +1,78 @@
+use pecs::core::task::Tasks;
+use pecs::prelude::*;
+use pecs::testing::{PromiseTestApp, TestPromise};
+use std::{
+    future,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Runs frames until the `promise` is not pending any more
+fn wait<S, R>(app: &mut PromiseTestApp, promise: &TestPromise<S, R>) {
+    let started = Instant::now();
+    while promise.handle().is_pending() {
+        assert!(started.elapsed() < Duration::from_secs(5), "task didn't finish in time");
+        app.step_frames(1);
+        thread::sleep(Duration::from_millis(1));
+    }
+}
+
+/// Sets the flag when dropped with the task future
+struct DropFlag(Arc<AtomicBool>);
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.store(true, Ordering::SeqCst);
+    }
+}
+
+#[test]
+fn spawn_resolves_with_output() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(asyn::task::spawn(async move { 2 + 2 }));
+    wait(&mut app, &promise);
+    app.assert_resolved(&promise, 4);
+    assert!(app.world.resource::<Tasks>().is_empty());
+}
+
+#[test]
+fn compute_resolves_with_result() {
+    let mut app = PromiseTestApp::new();
+    let promise =
+        app.run(asyn::task::compute(|| (1..=10).sum::<u32>()).then(asyn!(_, sum => Promise::resolve(sum * 2))));
+    wait(&mut app, &promise);
+    app.assert_resolved(&promise, 110);
+}
+
+#[test]
+fn io_keeps_state() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(Promise::new("file", asyn!(s => s.asyn().task().io(async move { 42 }))));
+    wait(&mut app, &promise);
+    assert_eq!(promise.take(), Some(("file", 42)));
+}
+
+#[test]
+fn discarded_promise_cancels_task() {
+    let mut app = PromiseTestApp::new();
+    let dropped = Arc::new(AtomicBool::new(false));
+    let flag = DropFlag(dropped.clone());
+    let promise = app.run(asyn::task::spawn(async move {
+        let _flag = flag;
+        future::pending::<()>().await;
+    }));
+    app.step_frames(1);
+    assert_eq!(app.world.resource::<Tasks>().len(), 1);
+    promise.handle().cancel();
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+    assert!(app.world.resource::<Tasks>().is_empty());
+    let started = Instant::now();
+    while !dropped.load(Ordering::SeqCst) {
+        assert!(started.elapsed() < Duration::from_secs(5), "task is not cancelled");
+        thread::sleep(Duration::from_millis(1));
+    }
+}
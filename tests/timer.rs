@@ -0,0 +1,186 @@
+use bevy::prelude::*;
+use pecs::core::diagnostic::PendingPromises;
+use pecs::core::timer::Timers;
+use pecs::prelude::*;
+use pecs::testing::PromiseTestApp;
+use std::time::Duration;
+
+fn millis(millis: u64) -> Duration {
+    Duration::from_millis(millis)
+}
+
+#[test]
+fn timeout_accepts_seconds_and_duration() {
+    let mut app = PromiseTestApp::new();
+    let seconds = app.run(asyn::timeout(0.5));
+    let duration = app.run(asyn::timeout(millis(500)));
+    app.advance(millis(499));
+    app.assert_pending(&seconds);
+    app.assert_pending(&duration);
+    app.advance(millis(1));
+    app.assert_resolved(&seconds, ());
+    app.assert_resolved(&duration, ());
+    assert!(app.world.resource::<Timers>().is_empty());
+}
+
+#[test]
+fn virtual_timers_freeze_when_paused() {
+    let mut app = PromiseTestApp::new();
+    app.world.resource_mut::<Time<Virtual>>().pause();
+    let game = app.run(asyn::timeout(1.0));
+    let real = app.run(PromiseTimer::real_time().timeout(1.0));
+    app.advance(millis(2000));
+    app.assert_pending(&game);
+    app.assert_resolved(&real, ());
+
+    app.world.resource_mut::<Time<Virtual>>().unpause();
+    app.advance(millis(1000));
+    app.assert_resolved(&game, ());
+}
+
+#[test]
+fn time_scale_applies_to_chain() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(
+        asyn::timeout(1.0)
+            .then(asyn!(_ => asyn::timeout(1.0)))
+            .then(asyn!(_ => PromiseTimer::real_time().timeout(1.0)))
+            .time_scale(2.0),
+    );
+    app.advance(millis(500));
+    app.advance(millis(500));
+    app.assert_pending(&promise);
+    app.advance(millis(500));
+    app.assert_resolved(&promise, ());
+
+    let stopped = app.run(asyn::timeout(1.0).time_scale(0.));
+    app.advance(millis(10_000));
+    app.assert_pending(&stopped);
+}
+
+#[test]
+fn nested_timeout_in_scaled_chain() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(
+        Promise::start(asyn!(_ => {
+            asyn::timeout(1.0).then(asyn!(_ => asyn::timeout(2.0)))
+        }))
+        .time_scale(4.0),
+    );
+    let unscaled = app.run(asyn::timeout(1.0));
+    app.advance(millis(250));
+    app.assert_pending(&unscaled);
+    app.advance(millis(499));
+    app.assert_pending(&promise);
+    app.advance(millis(1));
+    app.assert_resolved(&promise, ());
+    app.advance(millis(250));
+    app.assert_resolved(&unscaled, ());
+}
+
+#[test]
+fn nested_chain_overrides_time_scale() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(
+        asyn::timeout(1.0)
+            .then(asyn!(_ => asyn::timeout(1.0).time_scale(0.5)))
+            .time_scale(2.0),
+    );
+    app.advance(millis(500));
+    app.advance(millis(1999));
+    app.assert_pending(&promise);
+    app.advance(millis(1));
+    app.assert_resolved(&promise, ());
+}
+
+#[test]
+fn time_scale_without_pending_promises() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(asyn::timeout(1.0).then(asyn!(_ => asyn::timeout(1.0))).time_scale(2.0));
+    app.advance(millis(500));
+    app.advance(millis(499));
+    app.assert_pending(&promise);
+    app.advance(millis(1));
+    app.assert_resolved(&promise, ());
+    assert!(!app.world.contains_resource::<PendingPromises>());
+}
+
+/// Time passes only when the player ends the turn
+#[derive(Resource, Default)]
+struct Turns(Duration);
+impl TimerClock for Turns {
+    fn elapsed(&self) -> Duration {
+        self.0
+    }
+}
+
+#[test]
+fn custom_clock() {
+    let mut app = PromiseTestApp::new();
+    app.init_resource::<Turns>();
+    let promise = app.run(PromiseTimer::<Turns>::new().timeout(2.0));
+    app.advance(millis(5000));
+    app.assert_pending(&promise);
+    app.world.resource_mut::<Turns>().0 += Duration::from_secs(2);
+    app.step_frames(1);
+    app.assert_resolved(&promise, ());
+    assert!(app.world.resource::<Timers<Turns>>().is_empty());
+}
+
+#[test]
+fn interval_skips_missed_ticks() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(asyn::timer::interval(1.0).take(3).collect());
+    app.advance(millis(1000));
+    app.advance(millis(2500));
+    app.assert_pending(&promise);
+    app.advance(millis(500));
+    app.assert_resolved(&promise, vec![1, 2, 4]);
+}
+
+#[test]
+fn scaled_interval() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(asyn::timer::interval(1.0).take(3).collect().time_scale(2.0));
+    app.advance(millis(500));
+    app.advance(millis(500));
+    app.assert_pending(&promise);
+    app.advance(millis(500));
+    app.assert_resolved(&promise, vec![1, 2, 3]);
+}
+
+#[test]
+fn timeout_discards_timer() {
+    let mut app = PromiseTestApp::new();
+    let promise = app.run(asyn::timeout(1.0));
+    promise.handle().cancel();
+    app.step_frames(1);
+    app.assert_discarded(&promise);
+    assert!(app.world.resource::<Timers>().is_empty());
+}
+
+#[test]
+fn discarded_timers_are_skipped() {
+    let mut app = PromiseTestApp::new();
+    let promises: Vec<_> = (1..=1000u64)
+        .rev()
+        .map(|ms| app.run(asyn::timeout(millis(ms))))
+        .collect();
+    for promise in promises.iter().step_by(2) {
+        promise.handle().cancel();
+    }
+    app.advance(millis(500));
+    for (promise, ms) in promises.iter().zip((1..=1000u64).rev()) {
+        if ms % 2 == 0 {
+            app.assert_discarded(promise);
+        } else if ms <= 500 {
+            app.assert_resolved(promise, ());
+        } else {
+            app.assert_pending(promise);
+        }
+    }
+    assert_eq!(app.world.resource::<Timers>().len(), 250);
+    app.advance(millis(500));
+    assert!(promises.iter().all(|promise| !promise.handle().is_pending()));
+    assert!(app.world.resource::<Timers>().is_empty());
+}